@@ -0,0 +1,840 @@
+use crate::error::ApiError;
+use crate::{logging, net, priority, version};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::Duration;
+
+pub const DEFAULT_PORT: u16 = 3838;
+pub const DEFAULT_HOST: &str = "127.0.0.1";
+/// Entry point of the TUI, relative to the repo root or the bundle's resource dir.
+pub const DEFAULT_SCRIPT: &str = "apps/tui/src/index.tsx";
+pub const DEFAULT_HEALTH_PATH: &str = "/health";
+pub const DEFAULT_DOCS_PATH: &str = "/";
+/// Printed by `yep api` once Bun.serve is listening.
+pub const DEFAULT_READY_BANNER: &str = "API server running at";
+pub const DEFAULT_STARTUP_TIMEOUT_MS: u64 = 15_000;
+pub const DEFAULT_HEALTH_POLL_INTERVAL_MS: u64 = 500;
+pub const DEFAULT_HEALTH_CHECK_INTERVAL_MS: u64 = 1000;
+pub const DEFAULT_HEALTH_PROBE_TIMEOUT_MS: u64 = 1000;
+/// How long a live server may keep failing readiness before it's treated as wedged.
+pub const DEFAULT_NOT_READY_RESTART_MS: u64 = 30_000;
+/// How long a stopping server gets after being asked to exit, before it is force-killed.
+pub const DEFAULT_STOP_TIMEOUT_MS: u64 = 5000;
+pub const DEFAULT_KILL_TIMEOUT_MS: u64 = 5000;
+pub const DEFAULT_KILL_RETRIES: u32 = 2;
+/// Beyond this, a kill that keeps failing isn't going to start working.
+pub const MAX_KILL_RETRIES: u32 = 10;
+pub const DEFAULT_MAX_RESTART_ATTEMPTS: u32 = 5;
+/// Crashes within the window that stop auto-restart altogether, whatever the policy.
+pub const DEFAULT_CIRCUIT_BREAKER_CRASHES: u32 = 5;
+pub const DEFAULT_CIRCUIT_BREAKER_WINDOW_MS: u64 = 60_000;
+pub const DEFAULT_TOGGLE_SHORTCUT: &str = "CmdOrCtrl+Shift+Y";
+pub const CONFIG_FILE: &str = "yep.toml";
+
+/// What the watcher does when the child exits without being asked to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RestartPolicy {
+    Never,
+    OnFailure { max_retries: u32 },
+    Always,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy::OnFailure {
+            max_retries: DEFAULT_MAX_RESTART_ATTEMPTS,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Whether another restart is allowed after `exit`, given the attempts made so far.
+    pub fn should_restart(&self, exit: ExitStatus, attempts: u32) -> bool {
+        match *self {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure { max_retries } => !exit.success() && attempts < max_retries,
+            RestartPolicy::Always => true,
+        }
+    }
+}
+
+/// What gets spawned for a server. Readiness, logging and lifecycle work the same either way.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LaunchMode {
+    /// `bun run <script_path> api --port N --host H`, for development.
+    #[default]
+    BunScript,
+    /// `<path> <args...> --port N --host H`, for a compiled release build.
+    Binary {
+        path: PathBuf,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Nothing is spawned: the app attaches to a server already listening on `host:port`,
+    /// e.g. one run from a terminal with hot reload, and never starts or kills it.
+    External,
+}
+
+/// A launch spelled out in full, replacing the bun or binary invocation. `{port}`, `{host}`,
+/// `{socket}` and `{token}` in the arguments are filled in at spawn time; nothing else is
+/// added apart from `extra_args`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandTemplate {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl CommandTemplate {
+    /// The arguments with the placeholders substituted.
+    pub fn render(&self, port: u16, host: &str, socket: Option<&Path>, token: &str) -> Vec<String> {
+        let socket = socket
+            .map(|path| path.to_string_lossy())
+            .unwrap_or_default();
+        self.args
+            .iter()
+            .map(|arg| {
+                arg.replace("{port}", &port.to_string())
+                    .replace("{host}", host)
+                    .replace("{socket}", &socket)
+                    .replace("{token}", token)
+            })
+            .collect()
+    }
+
+    /// Whether the server gets told where to listen, which readiness depends on.
+    fn passes(&self, placeholder: &str, flag: &str) -> bool {
+        self.args
+            .iter()
+            .any(|arg| arg.contains(placeholder) || arg.split('=').next() == Some(flag))
+    }
+}
+
+/// How startup decides the server is ready to take requests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessStrategy {
+    /// Poll the readiness path until it returns a success status.
+    #[default]
+    HttpGet,
+    /// Poll until a TCP connection to the port succeeds, for servers without `/health`.
+    TcpConnect,
+    /// Wait for the stdout banner only; requires `ready_banner` to be non-empty.
+    StdoutBanner,
+    /// Opt-in fast path for local development: no readiness check at all. The server counts
+    /// as running once it survives a moment after spawning, so a broken server is only
+    /// caught later by the health monitor.
+    None,
+}
+
+/// How the app and the server talk to each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+    #[default]
+    Tcp,
+    /// A Unix domain socket in the app's data directory, unreachable from the network.
+    /// Falls back to TCP on platforms without Unix sockets.
+    UnixSocket,
+}
+
+/// What the startup sweep does with API servers left behind by an earlier session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanSweep {
+    /// Don't look for them.
+    Off,
+    /// Let the UI list them with `find_orphans` and confirm before killing.
+    #[default]
+    Prompt,
+    /// Kill them before the app starts its own servers.
+    AutoKill,
+}
+
+#[derive(Default, Deserialize)]
+pub struct FileConfig {
+    pub port: Option<u16>,
+    /// Permit ports below 1024, which usually need elevated privileges.
+    pub allow_privileged_port: Option<bool>,
+    /// Address the server binds to; anything other than loopback exposes it on the network.
+    pub host: Option<String>,
+    pub transport: Option<Transport>,
+    pub bun_path: Option<PathBuf>,
+    pub launch_mode: Option<LaunchMode>,
+    /// Overrides `launch_mode` with a custom command line.
+    pub command: Option<CommandTemplate>,
+    pub working_dir: Option<PathBuf>,
+    pub script_path: Option<PathBuf>,
+    /// Liveness endpoint: answers as long as the process is up.
+    pub health_path: Option<String>,
+    /// Readiness endpoint: answers once the server can serve requests.
+    pub ready_path: Option<String>,
+    /// Build info endpoint, queried by `get_server_version`.
+    pub version_path: Option<String>,
+    /// Page `open_api_in_browser` opens, e.g. `/api/docs` for the Swagger UI.
+    pub docs_path: Option<String>,
+    /// Semver range the server's version should fall in, e.g. `^0.1`.
+    pub expected_version: Option<String>,
+    pub not_ready_restart_ms: Option<u64>,
+    pub readiness: Option<ReadinessStrategy>,
+    /// Status codes a probe accepts; when unset, any 2xx passes.
+    pub healthy_status_codes: Option<Vec<u16>>,
+    pub startup_timeout_ms: Option<u64>,
+    pub health_poll_interval_ms: Option<u64>,
+    pub health_probe_timeout_ms: Option<u64>,
+    /// Keep probing running servers after startup.
+    pub health_monitor: Option<bool>,
+    pub health_check_interval_ms: Option<u64>,
+    pub ready_banner: Option<String>,
+    /// Drain window: how long a stopping server may finish in-flight requests after being
+    /// asked to exit (SIGTERM, or Ctrl-Break on Windows), before it is force-killed. 0 kills
+    /// it right away. Formerly `stop_grace_ms`, which is still accepted.
+    #[serde(alias = "stop_grace_ms")]
+    pub stop_timeout_ms: Option<u64>,
+    /// Separate from the drain window: how long to wait for a force-kill to take effect.
+    pub kill_timeout_ms: Option<u64>,
+    /// Extra attempts when a force-kill fails for a reason other than a denied permission.
+    pub kill_retries: Option<u32>,
+    pub restart_policy: Option<RestartPolicy>,
+    pub circuit_breaker_crashes: Option<u32>,
+    pub circuit_breaker_window_ms: Option<u64>,
+    /// Start the default server as soon as the app is up.
+    pub autostart: Option<bool>,
+    /// Desktop notifications when a server becomes ready or crashes.
+    pub notifications: Option<bool>,
+    /// Closing the window hides it to the tray and leaves the servers running.
+    pub close_to_tray: Option<bool>,
+    /// Global shortcut that starts or stops the default server; empty disables it.
+    pub toggle_shortcut: Option<String>,
+    pub orphan_sweep: Option<OrphanSweep>,
+    /// Keep one API token in the OS keyring instead of a fresh one per launch.
+    pub persist_token: Option<bool>,
+    /// Filter for the app's own diagnostics; `RUST_LOG` wins when set.
+    pub log_level: Option<String>,
+    /// Regex picking the level out of a server output line, for `set_log_filter`: its
+    /// `level` group, or else its first. Common prefixes like `[WARN]` work without one.
+    pub output_level_pattern: Option<String>,
+    /// Regex an orphan's command line must match for the startup sweep, for servers
+    /// started through `command`; a plain substring works too. By default the sweep looks
+    /// for the bun invocation or binary `launch_mode` would start.
+    pub process_match: Option<String>,
+    /// Serve the app's own lifecycle metrics at `http://127.0.0.1:<port>/metrics`; off
+    /// when unset.
+    pub metrics_port: Option<u16>,
+    /// Reload this file whenever it is saved, as `reload_config` would.
+    pub watch_config: Option<bool>,
+    /// Appended to the spawn command after `--port` and `--host`, or `--socket`.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Run the server at a lower CPU priority: a nice value from 0 to 19 on Unix; any
+    /// value above 0 means below-normal priority on Windows.
+    pub nice: Option<i32>,
+}
+
+impl FileConfig {
+    /// Reads `yep.toml` at `path`. A missing file is empty; a malformed one is an error
+    /// the caller can surface to the user.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(raw) => toml::from_str(&raw)
+                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(FileConfig::default()),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn startup_timeout_ms(&self) -> u64 {
+        self.startup_timeout_ms
+            .unwrap_or(DEFAULT_STARTUP_TIMEOUT_MS)
+    }
+
+    pub fn health_poll_interval_ms(&self) -> u64 {
+        self.health_poll_interval_ms
+            .unwrap_or(DEFAULT_HEALTH_POLL_INTERVAL_MS)
+    }
+
+    pub fn health_probe_timeout_ms(&self) -> u64 {
+        self.health_probe_timeout_ms
+            .unwrap_or(DEFAULT_HEALTH_PROBE_TIMEOUT_MS)
+    }
+
+    pub fn health_check_interval_ms(&self) -> u64 {
+        self.health_check_interval_ms
+            .unwrap_or(DEFAULT_HEALTH_CHECK_INTERVAL_MS)
+    }
+
+    /// Stdout substring that signals readiness. An empty string disables banner detection.
+    pub fn ready_banner(&self) -> Option<String> {
+        let banner = self.ready_banner.as_deref().unwrap_or(DEFAULT_READY_BANNER);
+        (!banner.is_empty()).then(|| banner.to_string())
+    }
+
+    pub fn not_ready_restart_ms(&self) -> u64 {
+        self.not_ready_restart_ms
+            .unwrap_or(DEFAULT_NOT_READY_RESTART_MS)
+    }
+
+    pub fn stop_timeout_ms(&self) -> u64 {
+        self.stop_timeout_ms.unwrap_or(DEFAULT_STOP_TIMEOUT_MS)
+    }
+
+    pub fn kill_timeout_ms(&self) -> u64 {
+        self.kill_timeout_ms.unwrap_or(DEFAULT_KILL_TIMEOUT_MS)
+    }
+}
+
+/// The effective configuration: the file merged with environment overrides and defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    pub port: u16,
+    pub allow_privileged_port: bool,
+    pub host: String,
+    pub transport: Transport,
+    /// Explicit bun binary; when unset it is discovered on each launch.
+    pub bun_path: Option<PathBuf>,
+    pub launch_mode: LaunchMode,
+    /// When set, spawned instead of what `launch_mode` describes.
+    pub command: Option<CommandTemplate>,
+    pub working_dir: PathBuf,
+    pub script_path: PathBuf,
+    /// Liveness endpoint, polled by the watcher.
+    pub health_path: String,
+    /// Readiness endpoint, awaited during startup and polled by the watcher.
+    pub ready_path: String,
+    pub version_path: String,
+    pub docs_path: String,
+    /// `None` skips the compatibility check.
+    pub expected_version: Option<String>,
+    pub readiness: ReadinessStrategy,
+    pub healthy_status_codes: Option<Vec<u16>>,
+    pub startup_timeout_ms: u64,
+    pub health_poll_interval_ms: u64,
+    pub health_probe_timeout_ms: u64,
+    pub health_monitor: bool,
+    /// How often running servers are probed; checked on the watcher's 1s tick, so
+    /// shorter intervals round up to it.
+    pub health_check_interval_ms: u64,
+    /// How long a live server may fail readiness before the watcher restarts it.
+    pub not_ready_restart_ms: u64,
+    /// `None` disables banner detection.
+    pub ready_banner: Option<String>,
+    /// After SIGTERM or Ctrl-Break; 0 skips draining.
+    #[serde(alias = "stop_grace_ms")]
+    pub stop_timeout_ms: u64,
+    /// How long to wait for the OS to confirm the exit after a force-kill.
+    pub kill_timeout_ms: u64,
+    pub kill_retries: u32,
+    pub restart_policy: RestartPolicy,
+    /// This many crashes within `circuit_breaker_window_ms` stops auto-restart until the
+    /// next manual start.
+    pub circuit_breaker_crashes: u32,
+    pub circuit_breaker_window_ms: u64,
+    pub autostart: bool,
+    pub notifications: bool,
+    pub close_to_tray: bool,
+    pub toggle_shortcut: String,
+    pub orphan_sweep: OrphanSweep,
+    pub persist_token: bool,
+    pub log_level: String,
+    pub output_level_pattern: Option<String>,
+    pub process_match: Option<String>,
+    pub metrics_port: Option<u16>,
+    pub watch_config: bool,
+    pub extra_args: Vec<String>,
+    /// Extra environment variables for the child, on top of the inherited environment.
+    pub env: HashMap<String, String>,
+    pub nice: i32,
+}
+
+impl Config {
+    pub fn resolve(file: &FileConfig, resource_dir: Option<PathBuf>) -> Self {
+        let working_dir = resolve_working_dir(file, resource_dir.clone());
+        let script_path = resolve_script_path(file, &working_dir, resource_dir.as_deref());
+        let health_path = resolve_health_path(file);
+        Config {
+            port: resolve_port(file),
+            allow_privileged_port: file.allow_privileged_port.unwrap_or(false),
+            host: resolve_host(file),
+            transport: file.transport.unwrap_or_default(),
+            bun_path: resolve_bun_path(file),
+            launch_mode: file.launch_mode.clone().unwrap_or_default(),
+            command: file.command.clone(),
+            working_dir,
+            script_path,
+            ready_path: resolve_ready_path(file, &health_path),
+            health_path,
+            version_path: first_path(None, file.version_path.clone())
+                .unwrap_or_else(|| version::DEFAULT_VERSION_PATH.to_string()),
+            docs_path: first_path(None, file.docs_path.clone())
+                .unwrap_or_else(|| DEFAULT_DOCS_PATH.to_string()),
+            expected_version: file.expected_version.clone(),
+            readiness: file.readiness.unwrap_or_default(),
+            healthy_status_codes: file.healthy_status_codes.clone(),
+            startup_timeout_ms: file.startup_timeout_ms(),
+            health_poll_interval_ms: file.health_poll_interval_ms(),
+            health_probe_timeout_ms: file.health_probe_timeout_ms(),
+            health_monitor: file.health_monitor.unwrap_or(true),
+            health_check_interval_ms: file.health_check_interval_ms(),
+            not_ready_restart_ms: file.not_ready_restart_ms(),
+            ready_banner: file.ready_banner(),
+            stop_timeout_ms: file.stop_timeout_ms(),
+            kill_timeout_ms: file.kill_timeout_ms(),
+            kill_retries: file.kill_retries.unwrap_or(DEFAULT_KILL_RETRIES),
+            restart_policy: file.restart_policy.unwrap_or_default(),
+            circuit_breaker_crashes: file
+                .circuit_breaker_crashes
+                .unwrap_or(DEFAULT_CIRCUIT_BREAKER_CRASHES),
+            circuit_breaker_window_ms: file
+                .circuit_breaker_window_ms
+                .unwrap_or(DEFAULT_CIRCUIT_BREAKER_WINDOW_MS),
+            autostart: file.autostart.unwrap_or(false),
+            notifications: file.notifications.unwrap_or(true),
+            close_to_tray: file.close_to_tray.unwrap_or(false),
+            toggle_shortcut: file
+                .toggle_shortcut
+                .clone()
+                .unwrap_or_else(|| DEFAULT_TOGGLE_SHORTCUT.to_string()),
+            orphan_sweep: file.orphan_sweep.unwrap_or_default(),
+            persist_token: file.persist_token.unwrap_or(false),
+            log_level: file
+                .log_level
+                .clone()
+                .unwrap_or_else(|| logging::DEFAULT_LOG_LEVEL.to_string()),
+            output_level_pattern: file.output_level_pattern.clone(),
+            process_match: file.process_match.clone(),
+            metrics_port: file.metrics_port,
+            watch_config: file.watch_config.unwrap_or(false),
+            extra_args: file.extra_args.clone(),
+            env: file.env.clone(),
+            nice: file.nice.unwrap_or(0),
+        }
+    }
+
+    /// Whether the server is run outside the app, see `LaunchMode::External`.
+    pub fn is_external(&self) -> bool {
+        self.launch_mode == LaunchMode::External
+    }
+
+    /// Whether the server listens on a Unix socket rather than a TCP port.
+    pub fn uses_socket(&self) -> bool {
+        cfg!(unix) && self.transport == Transport::UnixSocket
+    }
+
+    /// Host the app itself uses to reach the server.
+    pub fn connect_host(&self) -> &str {
+        net::connect_host(&self.host)
+    }
+
+    /// Rejects values that would only fail later, naming the offending field.
+    pub fn validate(&self) -> Result<(), ApiError> {
+        let invalid = |field: &'static str, reason: &str| {
+            Err(ApiError::InvalidConfig {
+                field,
+                reason: reason.to_string(),
+            })
+        };
+        net::check_port(self.port, self.allow_privileged_port)?;
+        if !net::is_valid_host(&self.host) {
+            return invalid("host", "must be an IP address or hostname");
+        }
+        validate_extra_args(&self.extra_args)?;
+        if !logging::is_valid_level(&self.log_level) {
+            return invalid(
+                "log_level",
+                "must be a level like 'info' or a RUST_LOG filter",
+            );
+        }
+        if let Some(pattern) = &self.process_match {
+            match regex::Regex::new(pattern) {
+                // Would match every process on the machine.
+                Ok(regex) if regex.is_match("") => {
+                    return invalid("process_match", "must not match an empty command line")
+                }
+                Ok(_) => {}
+                Err(e) => return invalid("process_match", &e.to_string()),
+            }
+        }
+        if let Some(port) = self.metrics_port {
+            if let Err(e) = net::check_port(port, self.allow_privileged_port) {
+                return invalid("metrics_port", &e.to_string());
+            }
+            if port == self.port {
+                return invalid("metrics_port", "must differ from the API port");
+            }
+        }
+        if let Some(pattern) = &self.output_level_pattern {
+            match regex::Regex::new(pattern) {
+                Ok(regex) if regex.captures_len() > 1 => {}
+                Ok(_) => return invalid("output_level_pattern", "must capture the level"),
+                Err(e) => return invalid("output_level_pattern", &e.to_string()),
+            }
+        }
+        #[cfg(desktop)]
+        if !crate::shortcut::is_valid(&self.toggle_shortcut) {
+            return invalid(
+                "toggle_shortcut",
+                "must be a shortcut like 'CmdOrCtrl+Shift+Y'",
+            );
+        }
+        if self.bun_path.as_deref().is_some_and(|path| !path.is_file()) {
+            return invalid("bun_path", "does not exist");
+        }
+        if let LaunchMode::Binary { path, .. } = &self.launch_mode {
+            if !path.is_file() {
+                return invalid("launch_mode", "binary does not exist");
+            }
+        }
+        if let Some(command) = &self.command {
+            if self.is_external() {
+                return invalid("command", "has no effect with launch_mode external");
+            }
+            if command.program.trim().is_empty() {
+                return invalid("command", "program must not be empty");
+            }
+            if self.uses_socket() && !command.passes("{socket}", "--socket") {
+                return invalid(
+                    "command",
+                    "args must pass the socket, e.g. '--socket {socket}'",
+                );
+            }
+            if !self.uses_socket() && !command.passes("{port}", "--port") {
+                return invalid("command", "args must pass the port, e.g. '--port {port}'");
+            }
+        }
+        if !self.working_dir.is_dir() {
+            return invalid("working_dir", "is not a directory");
+        }
+        if !self.health_path.starts_with('/') {
+            return invalid("health_path", "must start with '/'");
+        }
+        if !self.ready_path.starts_with('/') {
+            return invalid("ready_path", "must start with '/'");
+        }
+        if !self.version_path.starts_with('/') {
+            return invalid("version_path", "must start with '/'");
+        }
+        if !self.docs_path.starts_with('/') {
+            return invalid("docs_path", "must start with '/'");
+        }
+        let range = self.expected_version.as_deref();
+        if range.is_some_and(|range| !version::is_valid_range(range)) {
+            return invalid("expected_version", "must be a semver range like '^0.1'");
+        }
+        let codes = self.healthy_status_codes.as_deref().unwrap_or_default();
+        if codes.iter().any(|code| !(100..=599).contains(code)) {
+            return invalid(
+                "healthy_status_codes",
+                "must be HTTP status codes (100-599)",
+            );
+        }
+        let timeouts = [
+            ("startup_timeout_ms", self.startup_timeout_ms),
+            ("health_poll_interval_ms", self.health_poll_interval_ms),
+            ("health_check_interval_ms", self.health_check_interval_ms),
+            ("health_probe_timeout_ms", self.health_probe_timeout_ms),
+            ("not_ready_restart_ms", self.not_ready_restart_ms),
+            ("kill_timeout_ms", self.kill_timeout_ms),
+            ("circuit_breaker_window_ms", self.circuit_breaker_window_ms),
+        ];
+        if let Some((field, _)) = timeouts.iter().find(|(_, ms)| *ms == 0) {
+            return invalid(field, "must be greater than zero");
+        }
+        if self.kill_retries > MAX_KILL_RETRIES {
+            return invalid("kill_retries", "must be at most 10");
+        }
+        if !priority::is_valid_nice(self.nice) {
+            return invalid("nice", "must be between 0 and 19");
+        }
+        if self.circuit_breaker_crashes == 0 {
+            return invalid("circuit_breaker_crashes", "must be greater than zero");
+        }
+        Ok(())
+    }
+
+    /// Whether going from `self` to `next` only takes effect once the server is respawned.
+    pub fn requires_restart(&self, next: &Config) -> bool {
+        self.changed_fields(next)
+            .iter()
+            .any(|field| RESTART_FIELDS.contains(&field.as_str()))
+    }
+
+    /// Names of the fields that differ between `self` and `next`, as they appear in
+    /// `yep.toml`.
+    pub fn changed_fields(&self, next: &Config) -> Vec<String> {
+        let (Ok(Value::Object(old)), Ok(Value::Object(new))) =
+            (serde_json::to_value(self), serde_json::to_value(next))
+        else {
+            return Vec::new();
+        };
+        new.into_iter()
+            .filter(|(field, value)| old.get(field) != Some(value))
+            .map(|(field, _)| field)
+            .collect()
+    }
+
+    pub fn stop_timeout(&self) -> Duration {
+        Duration::from_millis(self.stop_timeout_ms)
+    }
+
+    pub fn kill_timeout(&self) -> Duration {
+        Duration::from_millis(self.kill_timeout_ms)
+    }
+
+    pub fn health_check_interval(&self) -> Duration {
+        Duration::from_millis(self.health_check_interval_ms)
+    }
+
+    pub fn circuit_breaker_window(&self) -> Duration {
+        Duration::from_millis(self.circuit_breaker_window_ms)
+    }
+
+    pub fn not_ready_limit(&self) -> Duration {
+        Duration::from_millis(self.not_ready_restart_ms)
+    }
+}
+
+/// Settings only read when a server is spawned, so changing them means a restart.
+pub const RESTART_FIELDS: [&str; 13] = [
+    "port",
+    "host",
+    "transport",
+    "env",
+    "bun_path",
+    "launch_mode",
+    "command",
+    "extra_args",
+    "persist_token",
+    "working_dir",
+    "script_path",
+    "nice",
+    "output_level_pattern",
+];
+
+/// Flags the app always passes itself, which extra arguments must not repeat.
+const RESERVED_ARGS: [&str; 3] = ["--port", "--host", "--socket"];
+
+pub fn validate_extra_args(args: &[String]) -> Result<(), ApiError> {
+    let reserved = args.iter().find(|arg| {
+        let flag = arg.split('=').next().unwrap_or_default();
+        RESERVED_ARGS.contains(&flag)
+    });
+    match reserved {
+        Some(arg) => Err(ApiError::InvalidConfig {
+            field: "extra_args",
+            reason: format!("'{}' is set by the app and can't be overridden", arg),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Writes the given `fields` of `config` into `yep.toml`, leaving the rest of the file as
+/// it is. Anything else in `config` may come from the environment or be derived, and
+/// must not end up in the file.
+pub fn save(path: &Path, config: &Config, fields: &[String]) -> Result<(), String> {
+    let mut table = match std::fs::read_to_string(path) {
+        // A malformed file is what the caller is replacing.
+        Ok(raw) => raw.parse::<toml::Table>().unwrap_or_default(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml::Table::new(),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let mut values = match toml::Value::try_from(config).map_err(|e| e.to_string())? {
+        toml::Value::Table(values) => values,
+        _ => return Err("config didn't serialize to a table".to_string()),
+    };
+    for field in fields {
+        if field == "stop_timeout_ms" {
+            table.remove("stop_grace_ms");
+        }
+        match values.remove(field) {
+            Some(value) => {
+                table.insert(field.clone(), value);
+            }
+            // An omitted banner would mean "use the default" on the next load.
+            None if field == "ready_banner" => {
+                table.insert(field.clone(), toml::Value::String(String::new()));
+            }
+            // Unset options are left out of the TOML altogether.
+            None => {
+                table.remove(field);
+            }
+        }
+    }
+    let raw = toml::to_string_pretty(&table).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, raw).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Creates a commented `yep.toml` listing the defaults, unless one already exists.
+pub fn write_default(path: &Path) -> std::io::Result<()> {
+    if path.exists() {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let template = format!(
+        "# yep desktop settings. Uncomment a line to override its default.\n\
+         \n\
+         # port = {port}\n\
+         # allow_privileged_port = false\n\
+         # host = \"{host}\"\n\
+         # transport = \"tcp\"\n\
+         # bun_path = \"/path/to/bun\"\n\
+         # launch_mode = {{ type = \"binary\", path = \"/path/to/yep-api\", args = [] }}\n\
+         # launch_mode = {{ type = \"external\" }}\n\
+         # command = {{ program = \"deno\", \
+         args = [\"run\", \"-A\", \"server.ts\", \
+         \"--port\", \"{{port}}\", \"--host\", \"{{host}}\"] }}\n\
+         # working_dir = \"/path/to/yep\"\n\
+         # script_path = \"{script}\"\n\
+         # health_path = \"{health}\"\n\
+         # ready_path = \"{health}\"\n\
+         # version_path = \"{version}\"\n\
+         # docs_path = \"{docs}\"\n\
+         # expected_version = \"^0.1\"\n\
+         # readiness = \"http_get\"\n\
+         # healthy_status_codes = [200, 204]\n\
+         # startup_timeout_ms = {startup}\n\
+         # health_poll_interval_ms = {poll}\n\
+         # health_probe_timeout_ms = {probe}\n\
+         # health_monitor = true\n\
+         # health_check_interval_ms = {check}\n\
+         # not_ready_restart_ms = {not_ready}\n\
+         # ready_banner = \"{banner}\"\n\
+         # stop_timeout_ms = {stop}\n\
+         # kill_timeout_ms = {kill}\n\
+         # kill_retries = {kill_retries}\n\
+         # restart_policy = {{ type = \"on_failure\", max_retries = {retries} }}\n\
+         # circuit_breaker_crashes = {breaker_crashes}\n\
+         # circuit_breaker_window_ms = {breaker_window}\n\
+         # autostart = false\n\
+         # notifications = true\n\
+         # close_to_tray = false\n\
+         # toggle_shortcut = \"{shortcut}\"\n\
+         # orphan_sweep = \"prompt\"\n\
+         # process_match = \"server\\\\.ts\"\n\
+         # persist_token = false\n\
+         # log_level = \"{log_level}\"\n\
+         # output_level_pattern = '^\\[(\\w+)\\]'\n\
+         # metrics_port = 9464\n\
+         # watch_config = false\n\
+         # nice = 0\n\
+         \n\
+         # extra_args = [\"--verbose\"]\n\
+         \n\
+         # [env]\n\
+         # YEP_LOG_LEVEL = \"debug\"\n",
+        port = DEFAULT_PORT,
+        host = DEFAULT_HOST,
+        script = DEFAULT_SCRIPT,
+        health = DEFAULT_HEALTH_PATH,
+        docs = DEFAULT_DOCS_PATH,
+        version = version::DEFAULT_VERSION_PATH,
+        startup = DEFAULT_STARTUP_TIMEOUT_MS,
+        poll = DEFAULT_HEALTH_POLL_INTERVAL_MS,
+        check = DEFAULT_HEALTH_CHECK_INTERVAL_MS,
+        probe = DEFAULT_HEALTH_PROBE_TIMEOUT_MS,
+        not_ready = DEFAULT_NOT_READY_RESTART_MS,
+        banner = DEFAULT_READY_BANNER,
+        stop = DEFAULT_STOP_TIMEOUT_MS,
+        kill = DEFAULT_KILL_TIMEOUT_MS,
+        kill_retries = DEFAULT_KILL_RETRIES,
+        log_level = logging::DEFAULT_LOG_LEVEL,
+        shortcut = DEFAULT_TOGGLE_SHORTCUT,
+        retries = DEFAULT_MAX_RESTART_ATTEMPTS,
+        breaker_crashes = DEFAULT_CIRCUIT_BREAKER_CRASHES,
+        breaker_window = DEFAULT_CIRCUIT_BREAKER_WINDOW_MS,
+    );
+    std::fs::write(path, template)
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|raw| raw.trim().to_string())
+        .filter(|raw| !raw.is_empty())
+}
+
+/// Resolves the API port from `YEP_API_PORT`, then the config file, then the default.
+fn resolve_port(file: &FileConfig) -> u16 {
+    env_var("YEP_API_PORT")
+        .and_then(|raw| raw.parse().ok())
+        .or(file.port)
+        .unwrap_or(DEFAULT_PORT)
+}
+
+/// Resolves the bind host from `YEP_API_HOST`, then the config file, then the default.
+/// Values that aren't an IP address or hostname are ignored.
+fn resolve_host(file: &FileConfig) -> String {
+    env_var("YEP_API_HOST")
+        .into_iter()
+        .chain(file.host.clone())
+        .find(|host| net::is_valid_host(host))
+        .unwrap_or_else(|| DEFAULT_HOST.to_string())
+}
+
+/// Resolves an explicitly configured bun binary from `YEP_BUN_PATH`, then the config file.
+fn resolve_bun_path(file: &FileConfig) -> Option<PathBuf> {
+    env_var("YEP_BUN_PATH")
+        .map(PathBuf::from)
+        .or_else(|| file.bun_path.clone())
+}
+
+/// Resolves the child's working directory: the configured one, else the bundle's resource
+/// dir in release builds, else the current dir so `tauri dev` keeps working from the repo.
+fn resolve_working_dir(file: &FileConfig, resource_dir: Option<PathBuf>) -> PathBuf {
+    if let Some(dir) = &file.working_dir {
+        return dir.clone();
+    }
+    let fallback = || std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    if cfg!(debug_assertions) {
+        fallback()
+    } else {
+        resource_dir.unwrap_or_else(fallback)
+    }
+}
+
+/// Resolves the TUI script: the configured path (relative to the working dir), else the
+/// bundled copy in the resource dir, falling back to the dev-relative path in debug builds.
+fn resolve_script_path(
+    file: &FileConfig,
+    working_dir: &Path,
+    resource_dir: Option<&Path>,
+) -> PathBuf {
+    if let Some(path) = &file.script_path {
+        return working_dir.join(path);
+    }
+    match resource_dir {
+        Some(dir) if !cfg!(debug_assertions) => dir.join(DEFAULT_SCRIPT),
+        _ => working_dir.join(DEFAULT_SCRIPT),
+    }
+}
+
+/// Resolves the liveness path from `YEP_HEALTH_PATH`, then the config file, then the
+/// default. Paths that don't start with `/` are ignored.
+fn resolve_health_path(file: &FileConfig) -> String {
+    first_path(env_var("YEP_HEALTH_PATH"), file.health_path.clone())
+        .unwrap_or_else(|| DEFAULT_HEALTH_PATH.to_string())
+}
+
+/// Resolves the readiness path from `YEP_READY_PATH`, then the config file, falling back
+/// to the liveness path so servers with a single endpoint keep working.
+fn resolve_ready_path(file: &FileConfig, health_path: &str) -> String {
+    first_path(env_var("YEP_READY_PATH"), file.ready_path.clone())
+        .unwrap_or_else(|| health_path.to_string())
+}
+
+fn first_path(env: Option<String>, file: Option<String>) -> Option<String> {
+    env.into_iter()
+        .chain(file)
+        .find(|path| path.starts_with('/'))
+}
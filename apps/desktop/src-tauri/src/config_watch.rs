@@ -0,0 +1,78 @@
+use crate::{reload, ApiState};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc;
+
+/// Emitted with the changed fields after `yep.toml` was edited and reloaded.
+pub const CONFIG_CHANGED_EVENT: &str = "config-changed";
+
+/// Quiet period after the last change before reloading, so a save that lands in several
+/// writes is only read once it is complete.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Keeps `yep.toml` watched for as long as it is alive.
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// Reloads the config whenever the file at `path` changes. The directory is watched
+/// rather than the file, since editors often save by replacing it.
+pub fn start(app: &AppHandle, path: &Path) -> Result<ConfigWatcher, String> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| format!("{} has no parent directory", path.display()))?;
+    let name = path.file_name().map(|name| name.to_os_string());
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        let ours = event
+            .paths
+            .iter()
+            .any(|path| path.file_name() == name.as_deref());
+        if ours && !event.kind.is_access() {
+            let _ = tx.send(());
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+    tauri::async_runtime::spawn(debounce(app.clone(), rx));
+    Ok(ConfigWatcher { _watcher: watcher })
+}
+
+/// Runs a reload once changes stop arriving for `DEBOUNCE`. Ends when the watcher is
+/// dropped, which closes the channel.
+async fn debounce(app: AppHandle, mut changes: mpsc::UnboundedReceiver<()>) {
+    while changes.recv().await.is_some() {
+        loop {
+            match tokio::time::timeout(DEBOUNCE, changes.recv()).await {
+                Ok(Some(())) => continue,
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+        let state = app.state::<ApiState>();
+        match reload(&app, &state).await {
+            // Includes the app's own writes through `set_config`.
+            Ok(result) if result.is_empty() => {}
+            Ok(result) => {
+                tracing::info!(
+                    applied = ?result.applied,
+                    requires_restart = ?result.requires_restart,
+                    shadowed = ?result.shadowed,
+                    "config file changed; reloaded"
+                );
+                let _ = app.emit(CONFIG_CHANGED_EVENT, &result);
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "config file changed but couldn't be reloaded");
+                *state.config_error.lock().await = Some(e.to_string());
+            }
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::config::{Config, LaunchMode};
+use crate::error::ApiError;
+use crate::health::LatencySummary;
+use crate::logs::LogLine;
+use crate::runtime::{self, BunInfo};
+use crate::server::{ServerHandle, ServerStats, ServerStatus};
+use crate::{token, ApiState};
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+
+/// How much of each instance's output goes into a bundle.
+pub const DIAGNOSTIC_LOG_LINES: usize = 200;
+
+/// Everything support needs to make sense of a report, with secrets stripped so the UI
+/// can save or paste it as is.
+#[derive(Serialize)]
+pub struct DiagnosticsBundle {
+    pub app_version: String,
+    pub os: OsInfo,
+    /// The live config, with every `env` value replaced.
+    pub config: Config,
+    /// Set when `yep.toml` was malformed and the defaults are in effect instead.
+    pub config_error: Option<String>,
+    pub bun: Result<BunInfo, String>,
+    pub paths: Paths,
+    pub instances: Vec<InstanceDiagnostics>,
+}
+
+#[derive(Serialize)]
+pub struct OsInfo {
+    pub family: &'static str,
+    pub arch: &'static str,
+    /// E.g. `Linux (Ubuntu 24.04)`, when the OS reports it.
+    pub name: Option<String>,
+    pub kernel: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct Paths {
+    pub config: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub log_file: Option<PathBuf>,
+    pub working_dir: PathBuf,
+    pub script: PathBuf,
+    pub bun: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+pub struct InstanceDiagnostics {
+    pub name: String,
+    pub status: ServerStatus,
+    pub pid: Option<u32>,
+    pub port: u16,
+    pub socket: Option<PathBuf>,
+    pub uptime_secs: u64,
+    #[serde(flatten)]
+    pub stats: ServerStats,
+    pub latency: LatencySummary,
+    pub last_error: Option<ErrorReport>,
+    pub logs: Vec<LogLine>,
+}
+
+/// An `ApiError` as the UI would see it, after redaction: its stderr tail may echo secrets.
+#[derive(Serialize)]
+pub struct ErrorReport {
+    pub kind: &'static str,
+    pub message: String,
+}
+
+/// Strings that must never leave the machine: API tokens and `env` values.
+struct Secrets(Vec<String>);
+
+impl Secrets {
+    fn redact(&self, text: &str) -> String {
+        self.0.iter().fold(text.to_string(), |text, secret| {
+            token::redact(&text, Some(secret))
+        })
+    }
+
+    fn redact_all(&self, texts: &mut [String]) {
+        for text in texts {
+            *text = self.redact(text);
+        }
+    }
+}
+
+pub async fn collect<R: Runtime>(app: &AppHandle<R>, state: &ApiState) -> DiagnosticsBundle {
+    let config = state.config().await;
+    let instances = state.all_instances().await;
+
+    let mut secrets: Vec<String> = config.env.values().cloned().collect();
+    secrets.extend(state.persistent_token.lock().await.clone());
+    for server in &instances {
+        secrets.extend(server.token.lock().await.clone());
+    }
+    secrets.retain(|secret| !secret.is_empty());
+    // Longest first, so a secret containing another is masked whole.
+    secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+    let secrets = Secrets(secrets);
+
+    let mut redacted = Config::clone(&config);
+    for value in redacted.env.values_mut() {
+        *value = token::REDACTED.to_string();
+    }
+    secrets.redact_all(&mut redacted.extra_args);
+    if let LaunchMode::Binary { args, .. } = &mut redacted.launch_mode {
+        secrets.redact_all(args);
+    }
+    if let Some(command) = &mut redacted.command {
+        secrets.redact_all(&mut command.args);
+    }
+
+    let mut servers = Vec::with_capacity(instances.len());
+    for server in &instances {
+        servers.push(instance(server, &secrets).await);
+    }
+    servers.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let bun_path = config.bun_path.clone();
+    let bun = tauri::async_runtime::spawn_blocking(move || {
+        let path = runtime::find_bun(bun_path.as_deref())?;
+        let version = runtime::bun_version(&path)?;
+        Ok::<_, ApiError>(BunInfo { path, version })
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result.map_err(|e| e.to_string()));
+
+    DiagnosticsBundle {
+        app_version: app.package_info().version.to_string(),
+        os: OsInfo {
+            family: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            name: sysinfo::System::long_os_version(),
+            kernel: sysinfo::System::kernel_version(),
+        },
+        config_error: state.config_error.lock().await.clone(),
+        paths: Paths {
+            config: state.config_path.clone(),
+            data_dir: state.data_dir.clone(),
+            log_file: state
+                .log_file
+                .as_ref()
+                .map(|file| file.path().to_path_buf()),
+            working_dir: config.working_dir.clone(),
+            script: config.script_path.clone(),
+            bun: bun.as_ref().ok().map(|info| info.path.clone()),
+        },
+        bun,
+        config: redacted,
+        instances: servers,
+    }
+}
+
+async fn instance(server: &ServerHandle, secrets: &Secrets) -> InstanceDiagnostics {
+    let mut logs = server.logs.recent(Some(DIAGNOSTIC_LOG_LINES));
+    for line in &mut logs {
+        line.line = secrets.redact(&line.line);
+    }
+    let last_error = server
+        .last_error
+        .lock()
+        .await
+        .as_ref()
+        .map(|error| ErrorReport {
+            kind: error.kind(),
+            message: secrets.redact(&error.to_string()),
+        });
+    let status = *server.status.lock().await;
+    let pid = *server.pid.lock().await;
+    let port = *server.port.lock().await;
+    let socket = server.socket.lock().await.clone();
+    let uptime = server.uptime().await;
+    let stats = *server.stats.lock().await;
+    let latency = server.latencies.lock().await.summary();
+    InstanceDiagnostics {
+        name: server.name.clone(),
+        status,
+        pid,
+        port,
+        socket,
+        uptime_secs: uptime.map_or(0, |uptime| uptime.as_secs()),
+        stats,
+        latency,
+        last_error,
+        logs,
+    }
+}
@@ -0,0 +1,184 @@
+use crate::server::ServerStatus;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("Failed to start API server: {0}")]
+    SpawnFailed(String),
+    #[error("bun not found; searched: {}", join_paths(.searched))]
+    BunNotFound { searched: Vec<PathBuf> },
+    #[error("API server script not found at {}", .0.display())]
+    ScriptNotFound(PathBuf),
+    #[error("API server binary not found at {}", .0.display())]
+    BinaryNotFound(PathBuf),
+    #[error("Failed to get bun version: {0}")]
+    BunVersionFailed(String),
+    #[error("API server exited during startup ({status}){}", tail(.stderr))]
+    ExitedDuringStartup {
+        status: String,
+        /// Unset when the child was killed by a signal.
+        code: Option<i32>,
+        stderr: Option<String>,
+    },
+    #[error(
+        "API server failed to start within {} seconds{}{}",
+        *.timeout_ms as f64 / 1000.0,
+        rejected(.last_status, *.rejected_ms),
+        tail(.stderr)
+    )]
+    StartupTimeout {
+        timeout_ms: u64,
+        /// The last status a readiness probe got back, when the server answered at all.
+        last_status: Option<u16>,
+        /// How long it had been answering with error statuses.
+        rejected_ms: u64,
+        stderr: Option<String>,
+    },
+    #[error("API server exited unexpectedly ({status}){}", tail(.stderr))]
+    Crashed {
+        status: String,
+        stderr: Option<String>,
+    },
+    #[error(
+        "API server stayed up but not ready for {} seconds and was restarted",
+        *.not_ready_ms as f64 / 1000.0
+    )]
+    Unresponsive { not_ready_ms: u64 },
+    #[error("API server is stopping")]
+    Stopping,
+    #[error("API server startup was cancelled")]
+    Cancelled,
+    #[error("API server can't go from {from:?} to {to:?}")]
+    InvalidTransition {
+        from: ServerStatus,
+        to: ServerStatus,
+    },
+    #[error("Timed out waiting for the API server to finish {0:?}")]
+    TransitionTimeout(ServerStatus),
+    #[error("{0}")]
+    PortUnavailable(String),
+    #[error("Invalid port {port}: {reason}")]
+    InvalidPort { port: u16, reason: String },
+    #[error("API server socket unavailable: {0}")]
+    SocketUnavailable(String),
+    #[error("Invalid {field}: {reason}")]
+    InvalidConfig { field: &'static str, reason: String },
+    #[error("Failed to save settings: {0}")]
+    ConfigWrite(String),
+    #[error("Failed to load settings: {0}")]
+    ConfigRead(String),
+    #[error("Failed to stop API server: {0}")]
+    StopFailed(String),
+    #[error("API server (pid {pid}) was still running {timeout_ms}ms after being killed")]
+    KillTimeout { pid: u32, timeout_ms: u64 },
+    #[error("Not allowed to kill the API server (pid {pid})")]
+    KillDenied { pid: u32 },
+    #[error("Failed to kill the API server (pid {pid}) after {attempts} attempts: {reason}")]
+    KillFailed {
+        pid: u32,
+        attempts: u32,
+        reason: String,
+    },
+    #[error("Failed to query API server process: {0}")]
+    ProcessQuery(String),
+    #[error("No API server instance named '{0}'")]
+    UnknownInstance(String),
+    #[error("Invalid instance name '{0}'; use letters, digits, '-' or '_'")]
+    InvalidInstanceName(String),
+    #[error("API server is not running")]
+    NotRunning,
+    #[error("Failed to open the browser: {0}")]
+    OpenFailed(String),
+    #[error("API server is run externally; start and stop it where it was launched")]
+    ExternalMode,
+    #[error("Invalid API request: {0}")]
+    InvalidRequest(String),
+    #[error("API request failed: {0}")]
+    RequestFailed(String),
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl ApiError {
+    /// Stable discriminant the frontend can branch on.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ApiError::SpawnFailed(_) => "spawn_failed",
+            ApiError::BunNotFound { .. } => "bun_not_found",
+            ApiError::ScriptNotFound(_) => "script_not_found",
+            ApiError::BinaryNotFound(_) => "binary_not_found",
+            ApiError::BunVersionFailed(_) => "bun_version_failed",
+            ApiError::ExitedDuringStartup { .. } => "exited_during_startup",
+            ApiError::StartupTimeout { .. } => "startup_timeout",
+            ApiError::Crashed { .. } => "crashed",
+            ApiError::Unresponsive { .. } => "unresponsive",
+            ApiError::Stopping => "stopping",
+            ApiError::Cancelled => "cancelled",
+            ApiError::InvalidTransition { .. } => "invalid_transition",
+            ApiError::TransitionTimeout(_) => "transition_timeout",
+            ApiError::PortUnavailable(_) => "port_unavailable",
+            ApiError::InvalidPort { .. } => "invalid_port",
+            ApiError::SocketUnavailable(_) => "socket_unavailable",
+            ApiError::InvalidConfig { .. } => "invalid_config",
+            ApiError::ConfigWrite(_) => "config_write",
+            ApiError::ConfigRead(_) => "config_read",
+            ApiError::StopFailed(_) => "stop_failed",
+            ApiError::KillTimeout { .. } => "kill_timeout",
+            ApiError::KillDenied { .. } => "kill_denied",
+            ApiError::KillFailed { .. } => "kill_failed",
+            ApiError::ProcessQuery(_) => "process_query",
+            ApiError::UnknownInstance(_) => "unknown_instance",
+            ApiError::InvalidInstanceName(_) => "invalid_instance_name",
+            ApiError::NotRunning => "not_running",
+            ApiError::OpenFailed(_) => "open_failed",
+            ApiError::ExternalMode => "external_mode",
+            ApiError::InvalidRequest(_) => "invalid_request",
+            ApiError::RequestFailed(_) => "request_failed",
+            ApiError::Internal(_) => "internal",
+        }
+    }
+}
+
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn rejected(last_status: &Option<u16>, rejected_ms: u64) -> String {
+    match last_status {
+        Some(status) => format!(
+            ": it is up but responded {} for {:.1}s",
+            status,
+            rejected_ms as f64 / 1000.0
+        ),
+        None => String::new(),
+    }
+}
+
+fn tail(stderr: &Option<String>) -> String {
+    match stderr {
+        Some(stderr) => format!("\n\nstderr:\n{}", stderr),
+        None => String::new(),
+    }
+}
+
+/// Serializes as `{ kind, message }` so the frontend gets both a tag and readable text.
+/// Config and port errors also carry the offending `field`.
+impl Serialize for ApiError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("ApiError", 3)?;
+        s.serialize_field("kind", self.kind())?;
+        s.serialize_field("message", &self.to_string())?;
+        match self {
+            ApiError::InvalidConfig { field, .. } => s.serialize_field("field", field)?,
+            ApiError::InvalidPort { .. } => s.serialize_field("field", "port")?,
+            _ => s.skip_field("field")?,
+        }
+        s.end()
+    }
+}
@@ -0,0 +1,151 @@
+use crate::health::Health;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Listener, Runtime};
+
+pub const SERVER_EVENT: &str = "server-event";
+/// Emitted on every startup poll so the UI can show how long is left.
+pub const STARTUP_PROGRESS_EVENT: &str = "startup-progress";
+/// Emitted when a server's health summary flips.
+pub const HEALTH_EVENT: &str = "health-changed";
+/// Emitted when repeated crashes stop auto-restart, so the UI can offer a manual retry.
+pub const CIRCUIT_OPEN_EVENT: &str = "circuit-open";
+/// Emitted once startup has picked the port, which may differ from the configured one if
+/// that was taken or was 0.
+pub const PORT_ASSIGNED_EVENT: &str = "port-assigned";
+
+/// Lifecycle transitions pushed to the frontend so it doesn't have to poll.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    Starting,
+    Ready {
+        port: u16,
+    },
+    /// `code` is `None` when the process was killed by a signal.
+    Crashed {
+        code: Option<i32>,
+    },
+    /// The server was asked to exit and has up to `timeout_ms` to finish what it's doing
+    /// before it is force-killed.
+    Draining {
+        timeout_ms: u64,
+    },
+    Stopped,
+    /// Startup gave up waiting for the server to become ready.
+    TimedOut,
+    Restarting {
+        attempt: u32,
+    },
+    /// A start that nobody was awaiting, such as autostart, failed.
+    StartFailed {
+        message: String,
+    },
+    /// Something the user should know about that doesn't stop the server.
+    Warning {
+        message: String,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct ServerEventPayload {
+    instance: String,
+    #[serde(flatten)]
+    event: ServerEvent,
+}
+
+pub fn emit<R: Runtime>(app: &AppHandle<R>, instance: &str, event: ServerEvent) {
+    let payload = ServerEventPayload {
+        instance: instance.to_string(),
+        event,
+    };
+    let _ = app.emit(SERVER_EVENT, payload);
+}
+
+#[derive(Clone, Serialize)]
+struct StartupProgress<'a> {
+    instance: &'a str,
+    attempt: u32,
+    max_attempts: u32,
+    elapsed_ms: u64,
+}
+
+pub fn emit_progress<R: Runtime>(
+    app: &AppHandle<R>,
+    instance: &str,
+    attempt: u32,
+    max_attempts: u32,
+    elapsed_ms: u64,
+) {
+    let payload = StartupProgress {
+        instance,
+        attempt,
+        max_attempts,
+        elapsed_ms,
+    };
+    let _ = app.emit(STARTUP_PROGRESS_EVENT, payload);
+}
+
+#[derive(Clone, Serialize)]
+struct HealthChanged<'a> {
+    instance: &'a str,
+    health: Health,
+}
+
+pub fn emit_health<R: Runtime>(app: &AppHandle<R>, instance: &str, health: Health) {
+    let _ = app.emit(HEALTH_EVENT, HealthChanged { instance, health });
+}
+
+#[derive(Clone, Serialize)]
+struct CircuitOpen<'a> {
+    instance: &'a str,
+    crashes: u32,
+    window_ms: u64,
+}
+
+pub fn emit_circuit_open<R: Runtime>(
+    app: &AppHandle<R>,
+    instance: &str,
+    crashes: u32,
+    window_ms: u64,
+) {
+    let payload = CircuitOpen {
+        instance,
+        crashes,
+        window_ms,
+    };
+    let _ = app.emit(CIRCUIT_OPEN_EVENT, payload);
+}
+
+#[derive(Clone, Serialize)]
+struct PortAssigned<'a> {
+    instance: &'a str,
+    port: u16,
+    requested: u16,
+}
+
+pub fn emit_port_assigned<R: Runtime>(
+    app: &AppHandle<R>,
+    instance: &str,
+    port: u16,
+    requested: u16,
+) {
+    let payload = PortAssigned {
+        instance,
+        port,
+        requested,
+    };
+    let _ = app.emit(PORT_ASSIGNED_EVENT, payload);
+}
+
+/// Calls `handler` with the instance name and event for every server event, so Rust-side
+/// features react to the same transitions the frontend sees.
+pub fn listen<R: Runtime, F>(app: &AppHandle<R>, handler: F)
+where
+    F: Fn(String, ServerEvent) + Send + 'static,
+{
+    app.listen_any(SERVER_EVENT, move |event| {
+        if let Ok(payload) = serde_json::from_str::<ServerEventPayload>(event.payload()) {
+            handler(payload.instance, payload.event);
+        }
+    });
+}
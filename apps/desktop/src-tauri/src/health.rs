@@ -0,0 +1,233 @@
+use crate::net::{self, Endpoint};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+/// Latest probe results for a running server; `None` until the first probe completes.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct ProbeState {
+    /// The process answers its liveness endpoint.
+    pub live: Option<bool>,
+    /// The server answers its readiness endpoint and can take requests.
+    pub ready: Option<bool>,
+}
+
+/// Reachability summary for the UI's status dot, derived from the readiness probe.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Health {
+    #[default]
+    Unknown,
+    Healthy,
+    Unhealthy,
+}
+
+impl ProbeState {
+    pub fn health(&self) -> Health {
+        match self.ready {
+            Some(true) => Health::Healthy,
+            Some(false) => Health::Unhealthy,
+            None => Health::Unknown,
+        }
+    }
+}
+
+/// How many monitor probes `LatencyHistory` remembers.
+pub const LATENCY_SAMPLES: usize = 100;
+
+/// Timings of the most recent monitor probes, bounded to `LATENCY_SAMPLES`.
+#[derive(Debug, Default)]
+pub struct LatencyHistory(VecDeque<(Duration, bool)>);
+
+/// Latency of the passing probes among the last few, to tell a slow server from a down one.
+/// All fields are 0 when none of them passed.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct LatencySummary {
+    /// Probes the summary covers, passing or not.
+    pub samples: usize,
+    pub failures: usize,
+    pub min_ms: u64,
+    pub avg_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+impl LatencyHistory {
+    pub fn record(&mut self, latency: Duration, passed: bool) {
+        if self.0.len() == LATENCY_SAMPLES {
+            self.0.pop_front();
+        }
+        self.0.push_back((latency, passed));
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        let mut passed: Vec<u64> = self
+            .0
+            .iter()
+            .filter(|(_, passed)| *passed)
+            .map(|(latency, _)| latency.as_millis() as u64)
+            .collect();
+        passed.sort_unstable();
+        let mut summary = LatencySummary {
+            samples: self.0.len(),
+            failures: self.0.len() - passed.len(),
+            ..LatencySummary::default()
+        };
+        if let (Some(&min), Some(&max)) = (passed.first(), passed.last()) {
+            // Nearest rank: the smallest sample at or above 95% of the rest.
+            let rank = (passed.len() * 95).div_ceil(100);
+            summary.min_ms = min;
+            summary.max_ms = max;
+            summary.avg_ms = passed.iter().sum::<u64>() / passed.len() as u64;
+            summary.p95_ms = passed[rank.saturating_sub(1)];
+        }
+        summary
+    }
+}
+
+/// Response codes that count as a passing HTTP probe.
+#[derive(Clone, Debug, Default)]
+pub enum HealthyStatus {
+    /// Any 2xx response.
+    #[default]
+    Success,
+    /// Exactly these codes, e.g. `[200, 401]` for an auth-protected endpoint.
+    Codes(Vec<u16>),
+}
+
+impl HealthyStatus {
+    /// Builds the set from config; an empty or missing list keeps the 2xx default.
+    pub fn from_config(codes: Option<Vec<u16>>) -> Self {
+        match codes {
+            Some(codes) if !codes.is_empty() => HealthyStatus::Codes(codes),
+            _ => HealthyStatus::Success,
+        }
+    }
+
+    pub fn accepts(&self, status: reqwest::StatusCode) -> bool {
+        match self {
+            HealthyStatus::Success => status.is_success(),
+            HealthyStatus::Codes(codes) => codes.contains(&status.as_u16()),
+        }
+    }
+}
+
+/// What a readiness or liveness probe got back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProbeOutcome {
+    Passed,
+    /// The server is listening but answered with a status that doesn't count as healthy,
+    /// e.g. 503 while it warms up.
+    Rejected(u16),
+    /// Nothing answered: not listening yet, or too slow to respond.
+    Unreachable,
+}
+
+impl ProbeOutcome {
+    pub fn passed(self) -> bool {
+        self == ProbeOutcome::Passed
+    }
+
+    pub fn from_status(status: reqwest::StatusCode, healthy: &HealthyStatus) -> Self {
+        if healthy.accepts(status) {
+            ProbeOutcome::Passed
+        } else {
+            ProbeOutcome::Rejected(status.as_u16())
+        }
+    }
+
+    /// For probes that can only tell whether the server answered at all.
+    pub fn from_reachable(reachable: bool) -> Self {
+        if reachable {
+            ProbeOutcome::Passed
+        } else {
+            ProbeOutcome::Unreachable
+        }
+    }
+}
+
+/// Sends `GET http://{host}:{port}{path}` with a `timeout`, authenticating with `token`
+/// when the server requires one, and checks the status against `healthy`.
+pub async fn probe_http(
+    client: &reqwest::Client,
+    host: &str,
+    port: u16,
+    path: &str,
+    token: Option<&str>,
+    healthy: &HealthyStatus,
+    timeout: Duration,
+) -> ProbeOutcome {
+    let url = format!("http://{}{}", net::authority(host, port), path);
+    let mut request = client.get(&url).timeout(timeout);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    match request.send().await {
+        Ok(resp) => ProbeOutcome::from_status(resp.status(), healthy),
+        Err(_) => ProbeOutcome::Unreachable,
+    }
+}
+
+/// Outcome of a one-shot probe against a server the app may not be managing.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct ProbeResult {
+    /// An HTTP response came back, whatever its status.
+    pub reachable: bool,
+    pub status_code: Option<u16>,
+    /// Time until the response arrived, or until the probe gave up.
+    pub latency_ms: u64,
+}
+
+/// Sends a single `GET http://{host}:{port}{path}` and reports what came back.
+pub async fn probe_once(
+    client: &reqwest::Client,
+    host: &str,
+    port: u16,
+    path: &str,
+    timeout: Duration,
+) -> ProbeResult {
+    let url = format!("http://{}{}", net::authority(host, port), path);
+    let started = Instant::now();
+    let status_code = client
+        .get(&url)
+        .timeout(timeout)
+        .send()
+        .await
+        .ok()
+        .map(|resp| resp.status().as_u16());
+    ProbeResult {
+        reachable: status_code.is_some(),
+        status_code,
+        latency_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Whether a connection to `endpoint` succeeds within `timeout`.
+pub async fn probe_connect(endpoint: &Endpoint, timeout: Duration) -> bool {
+    match endpoint {
+        Endpoint::Tcp { host, port } => probe_tcp(host, *port, timeout).await,
+        Endpoint::Unix(path) => probe_unix(path, timeout).await,
+    }
+}
+
+#[cfg(unix)]
+async fn probe_unix(path: &std::path::Path, timeout: Duration) -> bool {
+    let connect = tokio::net::UnixStream::connect(path);
+    matches!(tokio::time::timeout(timeout, connect).await, Ok(Ok(_)))
+}
+
+#[cfg(not(unix))]
+async fn probe_unix(_path: &std::path::Path, _timeout: Duration) -> bool {
+    false
+}
+
+/// Whether a TCP connection to `host:port` succeeds within `timeout`.
+pub async fn probe_tcp(host: &str, port: u16, timeout: Duration) -> bool {
+    let connect = TcpStream::connect((host, port));
+    matches!(tokio::time::timeout(timeout, connect).await, Ok(Ok(_)))
+}
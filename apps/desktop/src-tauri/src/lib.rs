@@ -1,103 +1,1437 @@
-use serde::Serialize;
-use std::sync::Mutex;
-use tauri::{Manager, State};
+mod config;
+mod config_watch;
+mod diagnostics;
+mod error;
+mod events;
+mod health;
+mod logfile;
+mod logging;
+mod logs;
+mod metrics;
+mod net;
+mod notifications;
+mod orphans;
+mod pidfile;
+mod priority;
+mod process;
+mod proxy;
+mod runtime;
+mod server;
+mod settings;
+#[cfg(desktop)]
+mod shortcut;
+mod spawner;
+mod sync;
+#[cfg(test)]
+mod tests;
+mod token;
+mod tray;
+mod unwind;
+mod version;
+mod watcher;
 
+use config::{Config, OrphanSweep, RestartPolicy, Transport};
+use config_watch::ConfigWatcher;
+use diagnostics::DiagnosticsBundle;
+use error::ApiError;
+use events::ServerEvent;
+use health::{Health, HealthyStatus, LatencySummary, ProbeOutcome, ProbeResult, ProbeState};
+use logfile::LogFile;
+use logs::{LogFilter, LogLine, LogPage, LogSubscribers, SharedLogFilter};
+use net::{Endpoint, Exposure};
+use orphans::Orphan;
+use proxy::{ApiRequest, ApiResponse};
+use reqwest::StatusCode;
+use runtime::BunInfo;
+use serde::{Deserialize, Serialize};
+use server::{
+    InstanceStop, LaunchOptions, ServerHandle, ServerStats, ServerStatus, StartOutcome,
+    StartResult, StartupTiming, StopResult,
+};
+use spawner::{OsSpawner, ProcessSpawner};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager, Runtime, State};
+use tokio::sync::{Mutex, RwLock};
+use version::ServerVersion;
+
+const RESTART_GRACE: Duration = Duration::from_millis(500);
+const HTTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const HTTP_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Limit for `probe_server`, independent of the managed server's probe settings.
+const PROBE_SERVER_TIMEOUT: Duration = Duration::from_secs(3);
+/// Instance used when a command doesn't name one.
+pub(crate) const DEFAULT_INSTANCE: &str = "default";
+
+/// Optional per-call overrides for `start_api_server`.
+#[derive(Default, Deserialize)]
+struct StartParams {
+    startup_timeout_ms: Option<u64>,
+    health_poll_interval_ms: Option<u64>,
+    health_probe_timeout_ms: Option<u64>,
+    /// Merged on top of the configured environment.
+    env: Option<HashMap<String, String>>,
+    /// Appended after the configured extra arguments.
+    extra_args: Option<Vec<String>>,
+}
+
+impl StartParams {
+    async fn into_options(self, state: &ApiState) -> Result<LaunchOptions, ApiError> {
+        let extra_args = self.extra_args.unwrap_or_default();
+        config::validate_extra_args(&extra_args)?;
+        let mut options = state.launch_options().await;
+        if let Some(ms) = self.startup_timeout_ms {
+            options.timing.timeout = Duration::from_millis(ms);
+        }
+        if let Some(ms) = self.health_poll_interval_ms {
+            options.timing.poll_interval = Duration::from_millis(ms);
+        }
+        if let Some(ms) = self.health_probe_timeout_ms {
+            options.timing.probe_timeout = Duration::from_millis(ms);
+        }
+        options.env.extend(self.env.unwrap_or_default());
+        options.extra_args.extend(extra_args);
+        Ok(options)
+    }
+}
+
+/// Settings shared by every instance, plus the instances themselves.
 struct ApiState {
-    port: u16,
-    child: Mutex<Option<std::process::Child>>,
+    config: RwLock<Arc<Config>>,
+    /// Location of `yep.toml`, if the config dir could be resolved.
+    config_path: Option<PathBuf>,
+    /// Why `yep.toml` couldn't be loaded, reported through `get_config`.
+    config_error: Mutex<Option<String>>,
+    /// Directory holding the per-instance PID files.
+    data_dir: Option<PathBuf>,
+    log_subscribers: LogSubscribers,
+    log_filter: SharedLogFilter,
+    /// On-disk copy of every instance's output, when the log dir is usable.
+    log_file: Option<Arc<LogFile>>,
+    /// Shared by the health check and anything else that talks to the server.
+    http: reqwest::Client,
+    instances: Mutex<HashMap<String, Arc<ServerHandle>>>,
+    /// The keyring-backed token, cached after the first read; in memory only when the
+    /// keyring is unavailable.
+    persistent_token: Mutex<Option<String>>,
+    /// Whether the watcher keeps probing running servers; toggled at runtime.
+    health_monitor: AtomicBool,
+    spawner: Arc<dyn ProcessSpawner>,
+    /// Set once app exit has begun stopping the servers; the watcher neither reaps nor
+    /// restarts anything from then on.
+    shutting_down: AtomicBool,
+    /// Reloads `yep.toml` on change while `watch_config` is on.
+    config_watcher: std::sync::Mutex<Option<ConfigWatcher>>,
+    /// The `metrics_port` endpoint, while one is configured.
+    metrics_server: std::sync::Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+}
+
+impl ApiState {
+    /// Snapshot of the current configuration; a launch keeps using the one it started with.
+    async fn config(&self) -> Arc<Config> {
+        self.config.read().await.clone()
+    }
+
+    /// `Config::validate`, plus checks against the live instances, whose ports the config
+    /// alone doesn't know.
+    async fn validate(&self, config: &Config) -> Result<(), ApiError> {
+        config.validate()?;
+        let Some(metrics_port) = config.metrics_port else {
+            return Ok(());
+        };
+        for server in self.all_instances().await {
+            // The default instance moves to `config.port`, which `validate` has checked.
+            if server.name != DEFAULT_INSTANCE && *server.port.lock().await == metrics_port {
+                return Err(ApiError::InvalidConfig {
+                    field: "metrics_port",
+                    reason: format!("already used by instance '{}'", server.name),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes an already validated `new` the live config and pushes it to every instance.
+    async fn replace_config(&self, app: &AppHandle, new: Config) -> ConfigUpdate {
+        let old = std::mem::replace(&mut *self.config.write().await, Arc::new(new.clone()));
+        if old.health_monitor != new.health_monitor {
+            self.health_monitor
+                .store(new.health_monitor, Ordering::SeqCst);
+        }
+        #[cfg(desktop)]
+        if old.toggle_shortcut != new.toggle_shortcut {
+            shortcut::register(app, &new.toggle_shortcut);
+        }
+        if old.watch_config != new.watch_config {
+            self.watch_config(app, new.watch_config);
+        }
+        if old.metrics_port != new.metrics_port {
+            self.serve_metrics(app, new.metrics_port);
+        }
+        if old.log_level != new.log_level {
+            if let Err(e) = logging::set_level(&new.log_level) {
+                tracing::warn!(error = %e, "failed to apply log level");
+            }
+        }
+        let mut running = false;
+        for server in self.all_instances().await {
+            *server.restart_policy.lock().await = new.restart_policy;
+            if server.name == DEFAULT_INSTANCE && old.port != new.port {
+                *server.port.lock().await = new.port;
+            }
+            running |= *server.status.lock().await == ServerStatus::Running;
+        }
+        ConfigUpdate {
+            restart_required: running && old.requires_restart(&new),
+        }
+    }
+
+    /// Starts or stops watching `yep.toml` for hand edits.
+    fn watch_config(&self, app: &AppHandle, enabled: bool) {
+        let mut watcher = sync::lock(&self.config_watcher, "config watcher");
+        match (&self.config_path, enabled) {
+            (Some(path), true) if watcher.is_none() => match config_watch::start(app, path) {
+                Ok(started) => *watcher = Some(started),
+                Err(e) => tracing::warn!(error = %e, "failed to watch the config file"),
+            },
+            (_, false) => *watcher = None,
+            _ => {}
+        }
+    }
+
+    /// Replaces the metrics endpoint with one on `port`, or just stops it.
+    fn serve_metrics(&self, app: &AppHandle, port: Option<u16>) {
+        let mut server = sync::lock(&self.metrics_server, "metrics server");
+        if let Some(previous) = server.take() {
+            previous.abort();
+        }
+        *server = port.map(|port| tauri::async_runtime::spawn(metrics::serve(app.clone(), port)));
+    }
+
+    /// Probes `path` on the server's port or socket, accepting the configured healthy
+    /// status codes.
+    async fn probe_http(
+        &self,
+        config: &Config,
+        server: &ServerHandle,
+        path: &str,
+        timeout: Duration,
+    ) -> ProbeOutcome {
+        let healthy = HealthyStatus::from_config(config.healthy_status_codes.clone());
+        let token = server.token.lock().await.clone();
+        let (host, port) = match server.endpoint(config).await {
+            Endpoint::Tcp { host, port } => (host, port),
+            endpoint @ Endpoint::Unix(_) => {
+                let request = ApiRequest {
+                    method: "GET".to_string(),
+                    path: path.to_string(),
+                    body: None,
+                    headers: HashMap::new(),
+                };
+                let forward = proxy::forward(&self.http, &endpoint, request, token.as_deref());
+                return match tokio::time::timeout(timeout, forward).await {
+                    Ok(Ok(response)) => match StatusCode::from_u16(response.status) {
+                        Ok(status) => ProbeOutcome::from_status(status, &healthy),
+                        Err(_) => ProbeOutcome::Rejected(response.status),
+                    },
+                    _ => ProbeOutcome::Unreachable,
+                };
+            }
+        };
+        health::probe_http(
+            &self.http,
+            &host,
+            port,
+            path,
+            token.as_deref(),
+            &healthy,
+            timeout,
+        )
+        .await
+    }
+
+    /// Where `name` listens when the Unix socket transport is on.
+    fn socket_path(&self, name: &str) -> PathBuf {
+        let dir = self
+            .data_dir
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join("yep-desktop"));
+        dir.join("sockets").join(format!("{}.sock", name))
+    }
+
+    async fn launch_options(&self) -> LaunchOptions {
+        let config = self.config().await;
+        LaunchOptions {
+            timing: StartupTiming {
+                timeout: Duration::from_millis(config.startup_timeout_ms),
+                poll_interval: Duration::from_millis(config.health_poll_interval_ms),
+                probe_timeout: Duration::from_millis(config.health_probe_timeout_ms),
+            },
+            env: config.env.clone(),
+            extra_args: config.extra_args.clone(),
+        }
+    }
+
+    /// Looks up an instance, creating it with the shared defaults if it doesn't exist yet.
+    async fn instance(&self, name: &str) -> Result<Arc<ServerHandle>, ApiError> {
+        validate_instance_name(name)?;
+        let config = self.config().await;
+        let mut instances = self.instances.lock().await;
+        let server = instances.entry(name.to_string()).or_insert_with(|| {
+            let pid_file = self
+                .data_dir
+                .as_deref()
+                .map(|dir| pidfile::path_for(dir, name));
+            Arc::new(ServerHandle::new(
+                name.to_string(),
+                config.port,
+                config.restart_policy,
+                pid_file,
+            ))
+        });
+        Ok(server.clone())
+    }
+
+    /// The token shared by every launch when `persist_token` is set.
+    async fn persistent_token(&self) -> String {
+        let mut cached = self.persistent_token.lock().await;
+        if let Some(token) = cached.as_ref() {
+            return token.clone();
+        }
+        let loaded = tauri::async_runtime::spawn_blocking(token::load_or_create)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result);
+        let token = loaded.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "keyring unavailable; API token won't survive a restart");
+            token::generate()
+        });
+        cached.insert(token).clone()
+    }
+
+    /// PIDs of the children this session is managing, which are never orphans.
+    async fn managed_pids(&self) -> Vec<u32> {
+        let mut pids = Vec::new();
+        for server in self.all_instances().await {
+            pids.extend(*server.pid.lock().await);
+        }
+        pids
+    }
+
+    /// Looks up an instance that must already exist.
+    async fn existing(&self, name: &str) -> Result<Arc<ServerHandle>, ApiError> {
+        self.instances
+            .lock()
+            .await
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ApiError::UnknownInstance(name.to_string()))
+    }
+
+    async fn all_instances(&self) -> Vec<Arc<ServerHandle>> {
+        self.instances.lock().await.values().cloned().collect()
+    }
+}
+
+/// Instance names end up in file names, so keep them to a safe character set.
+fn validate_instance_name(name: &str) -> Result<(), ApiError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidInstanceName(name.to_string()))
+    }
+}
+
+fn instance_name(instance: Option<String>) -> String {
+    instance.unwrap_or_else(|| DEFAULT_INSTANCE.to_string())
 }
 
 #[derive(Serialize)]
 struct ApiInfo {
+    instance: String,
+    port: u16,
+    /// `None` when the server listens on `socket` instead.
+    url: Option<String>,
+    socket: Option<PathBuf>,
+    pid: Option<u32>,
+    /// Unix timestamp (seconds) when the current child was spawned.
+    started_at: Option<u64>,
+    uptime_secs: u64,
+    /// Send as `Authorization: Bearer <token>`; changes on every start.
+    token: Option<String>,
+    /// Derived from the bind host, so the UI can warn when other devices can connect.
+    exposure: Exposure,
+    externally_reachable: bool,
+}
+
+#[derive(Serialize)]
+struct StatusInfo {
+    instance: String,
+    status: ServerStatus,
+    pid: Option<u32>,
+    #[serde(flatten)]
+    probes: ProbeState,
+    health: Health,
+}
+
+#[tauri::command]
+async fn get_api_info(
+    state: State<'_, ApiState>,
+    instance: Option<String>,
+) -> Result<ApiInfo, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    let config = state.config().await;
+    let port = *server.port.lock().await;
+    let socket = server.socket.lock().await.clone();
+    let url = socket
+        .is_none()
+        .then(|| format!("http://{}", net::authority(config.connect_host(), port)));
+    let pid = server.child.lock().await.as_ref().map(|child| child.id());
+    let uptime = server.uptime().await;
+    let token = server.token.lock().await.clone();
+    let started_at = uptime
+        .and_then(|uptime| SystemTime::now().checked_sub(uptime))
+        .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+        .map(|at| at.as_secs());
+    let exposure = match socket {
+        Some(_) => Exposure::Loopback,
+        None => Exposure::of(&config.host),
+    };
+    Ok(ApiInfo {
+        instance: server.name.clone(),
+        port,
+        url,
+        socket,
+        pid,
+        started_at,
+        uptime_secs: uptime.map_or(0, |uptime| uptime.as_secs()),
+        token,
+        externally_reachable: exposure.externally_reachable(),
+        exposure,
+    })
+}
+
+/// Opens `path`, or the configured `docs_path`, on the running server in the system browser.
+#[tauri::command]
+async fn open_api_in_browser(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+    path: Option<String>,
+    instance: Option<String>,
+) -> Result<(), ApiError> {
+    use tauri_plugin_shell::ShellExt;
+
+    let server = state.existing(&instance_name(instance)).await?;
+    if *server.status.lock().await != ServerStatus::Running {
+        return Err(ApiError::NotRunning);
+    }
+    let config = state.config().await;
+    let path = path.unwrap_or_else(|| config.docs_path.clone());
+    if !path.starts_with('/') {
+        return Err(ApiError::InvalidRequest(
+            "path must start with '/'".to_string(),
+        ));
+    }
+    if server.socket.lock().await.is_some() {
+        return Err(ApiError::OpenFailed(
+            "the server listens on a Unix socket, which browsers can't reach".to_string(),
+        ));
+    }
+    let port = *server.port.lock().await;
+    let url = format!(
+        "http://{}{}",
+        net::authority(config.connect_host(), port),
+        path
+    );
+    // The opener plugin would be one more dependency for the same call.
+    #[allow(deprecated)]
+    app.shell()
+        .open(url, None)
+        .map_err(|e| ApiError::OpenFailed(e.to_string()))
+}
+
+#[tauri::command]
+async fn get_server_status(
+    state: State<'_, ApiState>,
+    instance: Option<String>,
+) -> Result<StatusInfo, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    let status = *server.status.lock().await;
+    let pid = *server.pid.lock().await;
+    let probes = *server.probes.lock().await;
+    Ok(StatusInfo {
+        instance: server.name.clone(),
+        status,
+        pid,
+        probes,
+        health: probes.health(),
+    })
+}
+
+/// How often `snapshot` re-reads an instance whose status changed under it.
+const SNAPSHOT_ATTEMPTS: usize = 3;
+
+/// One row of `get_all_instances`.
+#[derive(Serialize)]
+struct InstanceSnapshot {
+    name: String,
+    port: u16,
+    socket: Option<PathBuf>,
+    status: ServerStatus,
+    pid: Option<u32>,
+    uptime_secs: u64,
+    crash_count: u32,
+    /// From the health monitor's last probe; nothing is probed for the snapshot.
+    health: Health,
+    /// The most recent output line, for a glance at what the server is doing.
+    last_line: Option<LogLine>,
+}
+
+/// Reads `server` field by field, retrying while its status changes mid-read (e.g. a
+/// crash or a start finishing) so the fields agree with each other. The locks are taken
+/// one at a time, as everywhere else, so this can't deadlock against a transition.
+async fn snapshot(server: &ServerHandle) -> InstanceSnapshot {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let status = *server.status.lock().await;
+        let port = *server.port.lock().await;
+        let socket = server.socket.lock().await.clone();
+        let pid = *server.pid.lock().await;
+        let uptime = server.uptime().await;
+        let crash_count = server.stats.lock().await.crash_count;
+        let health = server.probes.lock().await.health();
+        if attempt < SNAPSHOT_ATTEMPTS && *server.status.lock().await != status {
+            continue;
+        }
+        return InstanceSnapshot {
+            name: server.name.clone(),
+            port,
+            socket,
+            status,
+            pid,
+            uptime_secs: uptime.map_or(0, |uptime| uptime.as_secs()),
+            crash_count,
+            health,
+            last_line: server.logs.recent(Some(1)).pop(),
+        };
+    }
+}
+
+/// Every instance at once, ordered by name, so a dashboard needs a single call.
+#[tauri::command]
+async fn get_all_instances(state: State<'_, ApiState>) -> Result<Vec<InstanceSnapshot>, ApiError> {
+    // Cloned out so the map isn't locked while each instance is read.
+    let mut instances = state.all_instances().await;
+    instances.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut snapshots = Vec::with_capacity(instances.len());
+    for server in &instances {
+        snapshots.push(snapshot(server).await);
+    }
+    Ok(snapshots)
+}
+
+/// Sends an HTTP request to a running instance on the frontend's behalf.
+#[tauri::command]
+async fn api_request(
+    state: State<'_, ApiState>,
+    method: String,
+    path: String,
+    body: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    instance: Option<String>,
+) -> Result<ApiResponse, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    // A draining server is finishing what it has; don't hand it anything new.
+    match *server.status.lock().await {
+        ServerStatus::Running => {}
+        ServerStatus::Stopping => return Err(ApiError::Stopping),
+        _ => return Err(ApiError::NotRunning),
+    }
+    let config = state.config().await;
+    let endpoint = server.endpoint(&config).await;
+    let token = server.token.lock().await.clone();
+    let request = ApiRequest {
+        method,
+        path,
+        body,
+        headers: headers.unwrap_or_default(),
+    };
+    unwind::guard(proxy::forward(
+        &state.http,
+        &endpoint,
+        request,
+        token.as_deref(),
+    ))
+    .await
+}
+
+/// The running server's build info, checked against the configured version range.
+#[tauri::command]
+async fn get_server_version(
+    state: State<'_, ApiState>,
+    instance: Option<String>,
+) -> Result<ServerVersion, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    let config = state.config().await;
+    if !config.is_external() && *server.status.lock().await != ServerStatus::Running {
+        return Err(ApiError::NotRunning);
+    }
+    let endpoint = server.endpoint(&config).await;
+    let token = server.token.lock().await.clone();
+    unwind::guard(version::fetch(
+        &state.http,
+        &endpoint,
+        &config.version_path,
+        token.as_deref(),
+        config.expected_version.as_deref(),
+    ))
+    .await
+}
+
+/// Probes a server started outside the app, e.g. from a terminal, without touching any
+/// managed instance. `path` defaults to the configured health path.
+#[tauri::command]
+async fn probe_server(
+    state: State<'_, ApiState>,
     port: u16,
-    url: String,
+    path: Option<String>,
+) -> Result<ProbeResult, ApiError> {
+    let config = state.config().await;
+    let path = path.unwrap_or_else(|| config.health_path.clone());
+    Ok(health::probe_once(
+        &state.http,
+        config.connect_host(),
+        port,
+        &path,
+        PROBE_SERVER_TIMEOUT,
+    )
+    .await)
 }
 
 #[tauri::command]
-fn get_api_info(state: State<ApiState>) -> ApiInfo {
-    ApiInfo {
-        port: state.port,
-        url: format!("http://localhost:{}", state.port),
+fn start_health_monitor(state: State<'_, ApiState>) {
+    state.health_monitor.store(true, Ordering::SeqCst);
+}
+
+/// Pauses background probing. Health goes back to unknown rather than showing stale results.
+#[tauri::command]
+async fn stop_health_monitor(app: AppHandle, state: State<'_, ApiState>) -> Result<(), ApiError> {
+    state.health_monitor.store(false, Ordering::SeqCst);
+    for server in state.all_instances().await {
+        server.reset_probes(&app).await;
     }
+    Ok(())
+}
+
+/// The persistent API token, for configuring external tools.
+#[tauri::command]
+async fn get_or_create_api_token(state: State<'_, ApiState>) -> Result<String, ApiError> {
+    Ok(state.persistent_token().await)
 }
 
+/// Replaces the persistent API token and restarts running servers so they pick it up.
 #[tauri::command]
-async fn start_api_server(state: State<'_, ApiState>) -> Result<String, String> {
-    {
-        let child_lock = state.child.lock().map_err(|e| e.to_string())?;
-        if child_lock.is_some() {
-            return Ok(format!("API server already running on port {}", state.port));
+async fn rotate_api_token(app: AppHandle, state: State<'_, ApiState>) -> Result<String, ApiError> {
+    let token = token::generate();
+    let stored = token.clone();
+    let saved = tauri::async_runtime::spawn_blocking(move || token::store(&stored))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result);
+    if let Err(e) = saved {
+        tracing::warn!(error = %e, "keyring unavailable; rotated API token kept in memory");
+    }
+    *state.persistent_token.lock().await = Some(token.clone());
+    if state.config().await.persist_token {
+        for server in state.all_instances().await {
+            if *server.status.lock().await == ServerStatus::Running {
+                restart(&app, &state, &server).await?;
+            }
         }
     }
+    Ok(token)
+}
 
-    let port = state.port;
-    let child = std::process::Command::new("bun")
-        .args([
-            "run",
-            "apps/tui/src/index.tsx",
-            "api",
-            "--port",
-            &port.to_string(),
-        ])
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to start API server: {}", e))?;
+#[derive(Serialize)]
+struct StatsInfo {
+    instance: String,
+    #[serde(flatten)]
+    stats: ServerStats,
+    uptime_secs: u64,
+}
+
+#[tauri::command]
+async fn get_server_stats(
+    state: State<'_, ApiState>,
+    instance: Option<String>,
+) -> Result<StatsInfo, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    let stats = *server.stats.lock().await;
+    let uptime = server.uptime().await;
+    Ok(StatsInfo {
+        instance: server.name.clone(),
+        stats,
+        uptime_secs: uptime.map_or(0, |uptime| uptime.as_secs()),
+    })
+}
+
+#[derive(Serialize)]
+struct HealthStats {
+    instance: String,
+    #[serde(flatten)]
+    latency: LatencySummary,
+}
+
+/// Latency of the health monitor's recent liveness probes against the current child.
+#[tauri::command]
+async fn get_health_stats(
+    state: State<'_, ApiState>,
+    instance: Option<String>,
+) -> Result<HealthStats, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    let latency = server.latencies.lock().await.summary();
+    Ok(HealthStats {
+        instance: server.name.clone(),
+        latency,
+    })
+}
+
+#[derive(Serialize)]
+struct ConfigInfo {
+    config: Config,
+    path: Option<PathBuf>,
+    /// Set when `yep.toml` was malformed and the defaults are in effect instead.
+    error: Option<String>,
+}
+
+#[tauri::command]
+async fn get_config(state: State<'_, ApiState>) -> Result<ConfigInfo, ApiError> {
+    Ok(ConfigInfo {
+        config: Config::clone(&*state.config().await),
+        path: state.config_path.clone(),
+        error: state.config_error.lock().await.clone(),
+    })
+}
+
+#[derive(Serialize)]
+struct ConfigUpdate {
+    /// A running server is still using settings that only apply after a restart.
+    restart_required: bool,
+}
 
-    {
-        let mut child_lock = state.child.lock().map_err(|e| e.to_string())?;
-        *child_lock = Some(child);
+/// Validates and persists `new`, then makes it the live config.
+async fn apply_config(
+    app: &AppHandle,
+    state: &ApiState,
+    new: Config,
+) -> Result<ConfigUpdate, ApiError> {
+    state.validate(&new).await?;
+    let path = state
+        .config_path
+        .as_deref()
+        .ok_or_else(|| ApiError::ConfigWrite("no config directory".to_string()))?;
+    let changed = state.config().await.changed_fields(&new);
+    config::save(path, &new, &changed).map_err(ApiError::ConfigWrite)?;
+    settings::forget(app, &changed).map_err(ApiError::ConfigWrite)?;
+    *state.config_error.lock().await = None;
+    Ok(state.replace_config(app, new).await)
+}
+
+#[tauri::command]
+async fn set_config(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+    new: Config,
+) -> Result<ConfigUpdate, ApiError> {
+    unwind::guard(apply_config(&app, &state, new)).await
+}
+
+#[derive(Serialize)]
+struct ConfigReloadResult {
+    /// Changed fields that are already in effect.
+    applied: Vec<String>,
+    /// Changed fields a running server only picks up once it is restarted.
+    requires_restart: Vec<String>,
+    /// Fields set in `yep.toml` but overridden by the settings store the UI edits.
+    shadowed: Vec<String>,
+}
+
+impl ConfigReloadResult {
+    fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.requires_restart.is_empty() && self.shadowed.is_empty()
     }
+}
 
-    let url = format!("http://localhost:{}/health", port);
-    let client = reqwest::Client::new();
-    for _ in 0..30 {
-        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-        if let Ok(resp) = client.get(&url).send().await {
-            if resp.status().is_success() {
-                return Ok(format!("API server started on port {}", port));
+/// Re-reads `yep.toml` and makes it the live config. A file that fails to parse or
+/// validate leaves the current config as it is.
+async fn reload(app: &AppHandle, state: &ApiState) -> Result<ConfigReloadResult, ApiError> {
+    let path = state
+        .config_path
+        .as_deref()
+        .ok_or_else(|| ApiError::ConfigRead("no config directory".to_string()))?;
+    let mut file = config::FileConfig::load(path).map_err(ApiError::ConfigRead)?;
+    let shadowed = match settings::open(app) {
+        Some(store) => settings::StoreSettings::load(&store).overlay(&mut file),
+        None => Vec::new(),
+    };
+    let new = Config::resolve(&file, app.path().resource_dir().ok());
+    state.validate(&new).await?;
+
+    let changed = state.config().await.changed_fields(&new);
+    *state.config_error.lock().await = None;
+    let update = state.replace_config(app, new).await;
+    let (requires_restart, applied) = changed.into_iter().partition(|field| {
+        update.restart_required && config::RESTART_FIELDS.contains(&field.as_str())
+    });
+    Ok(ConfigReloadResult {
+        applied,
+        requires_restart,
+        shadowed: shadowed.into_iter().map(String::from).collect(),
+    })
+}
+
+/// Picks up hand edits to `yep.toml` without restarting the app.
+#[tauri::command]
+async fn reload_config(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+) -> Result<ConfigReloadResult, ApiError> {
+    unwind::guard(reload(&app, &state)).await
+}
+
+/// Like `set_config`, but restarts every running server when the change needs it.
+#[tauri::command]
+async fn set_config_and_restart(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+    new: Config,
+) -> Result<ConfigUpdate, ApiError> {
+    let update = unwind::guard(apply_config(&app, &state, new)).await?;
+    if update.restart_required {
+        for server in state.all_instances().await {
+            if *server.status.lock().await == ServerStatus::Running {
+                restart(&app, &state, &server).await?;
             }
         }
     }
+    Ok(ConfigUpdate {
+        restart_required: false,
+    })
+}
+
+/// The most recent start, stop, or crash failure for an instance, if any.
+#[tauri::command]
+async fn get_last_error(
+    state: State<'_, ApiState>,
+    instance: Option<String>,
+) -> Result<Option<ApiError>, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    let error = server.last_error.lock().await.clone();
+    Ok(error)
+}
+
+#[tauri::command]
+async fn clear_last_error(
+    state: State<'_, ApiState>,
+    instance: Option<String>,
+) -> Result<(), ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    *server.last_error.lock().await = None;
+    Ok(())
+}
+
+/// API servers left running by an earlier session, for the UI to offer to kill.
+#[tauri::command]
+async fn find_orphans(state: State<'_, ApiState>) -> Result<Vec<Orphan>, ApiError> {
+    unwind::guard(async {
+        let config = state.config().await;
+        let managed = state.managed_pids().await;
+        // Scans the whole process table.
+        tauri::async_runtime::spawn_blocking(move || orphans::find(&config, &managed))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    })
+    .await
+}
+
+/// Kills the listed orphans that still match, returning the PIDs that were killed.
+#[tauri::command]
+async fn kill_orphans(state: State<'_, ApiState>, pids: Vec<u32>) -> Result<Vec<u32>, ApiError> {
+    unwind::guard(async {
+        let config = state.config().await;
+        let managed = state.managed_pids().await;
+        // Retrying a failed kill sleeps between attempts.
+        tauri::async_runtime::spawn_blocking(move || orphans::kill(&pids, &config, &managed))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn check_bun(state: State<'_, ApiState>) -> Result<BunInfo, ApiError> {
+    let bun_path = state.config().await.bun_path.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = runtime::find_bun(bun_path.as_deref())?;
+        let version = runtime::bun_version(&path)?;
+        Ok(BunInfo { path, version })
+    })
+    .await
+    .map_err(|e| ApiError::BunVersionFailed(e.to_string()))?
+}
+
+/// A redacted snapshot of config, state, recent logs and environment, for bug reports.
+#[tauri::command]
+async fn collect_diagnostics(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+) -> Result<DiagnosticsBundle, ApiError> {
+    Ok(diagnostics::collect(&app, &state).await)
+}
+
+#[tauri::command]
+async fn set_restart_policy(
+    state: State<'_, ApiState>,
+    policy: RestartPolicy,
+    instance: Option<String>,
+) -> Result<(), ApiError> {
+    let server = state.instance(&instance_name(instance)).await?;
+    *server.restart_policy.lock().await = policy;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_logs(
+    state: State<'_, ApiState>,
+    limit: Option<usize>,
+    instance: Option<String>,
+) -> Result<Vec<LogLine>, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    Ok(server.logs.recent(limit))
+}
+
+/// Pages through an instance's buffered output by sequence number, so lines arriving
+/// while the UI scrolls don't shift what it has already seen.
+#[tauri::command]
+async fn get_logs_page(
+    state: State<'_, ApiState>,
+    offset: u64,
+    limit: usize,
+    instance: Option<String>,
+) -> Result<LogPage, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    Ok(server.logs.page(offset, limit))
+}
+
+#[tauri::command]
+async fn clear_logs(state: State<'_, ApiState>, instance: Option<String>) -> Result<(), ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    server.logs.clear();
+    Ok(())
+}
+
+/// The app's own lifecycle counters in the Prometheus text format, as served on
+/// `metrics_port`.
+#[tauri::command]
+async fn get_metrics_text(state: State<'_, ApiState>) -> Result<String, ApiError> {
+    Ok(metrics::render(&state).await)
+}
+
+/// Narrows what `get_logs` and the log subscribers see from now on, across instances.
+/// Lines already buffered stay; the log file keeps everything.
+#[tauri::command]
+fn set_log_filter(state: State<'_, ApiState>, filter: LogFilter) {
+    *sync::lock(&state.log_filter, "log filter") = filter;
+}
+
+/// Streams live output over `channel` until `unsubscribe_logs` is called or the channel
+/// goes away. Returns the subscription id.
+#[tauri::command]
+fn subscribe_logs(
+    state: State<'_, ApiState>,
+    channel: Channel<LogLine>,
+    instance: Option<String>,
+) -> u32 {
+    state.log_subscribers.subscribe(instance, channel)
+}
+
+#[tauri::command]
+fn unsubscribe_logs(state: State<'_, ApiState>, id: u32) -> bool {
+    state.log_subscribers.unsubscribe(id)
+}
+
+/// Where server output is persisted, so the UI can offer to open the folder.
+#[tauri::command]
+fn get_log_file_path(state: State<'_, ApiState>) -> Option<PathBuf> {
+    state
+        .log_file
+        .as_ref()
+        .map(|file| file.path().to_path_buf())
+}
+
+#[tauri::command]
+async fn start_api_server(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+    params: Option<StartParams>,
+    instance: Option<String>,
+) -> Result<StartResult, ApiError> {
+    start_api_server_detailed(app, state, params, instance)
+        .await?
+        .into_result()
+}
+
+/// Like `start_api_server`, but a server that times out or dies while starting is
+/// reported with its output instead of as an error, for scripts that decide what to do
+/// next themselves.
+#[tauri::command]
+async fn start_api_server_detailed(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+    params: Option<StartParams>,
+    instance: Option<String>,
+) -> Result<StartOutcome, ApiError> {
+    let server = state.instance(&instance_name(instance)).await?;
+    let options = params.unwrap_or_default().into_options(&state).await?;
+    start_detailed(&app, &state, &server, options).await
+}
+
+/// Runs `start`, sorting a failed startup into its `StartOutcome`.
+async fn start_detailed<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &ApiState,
+    server: &ServerHandle,
+    options: LaunchOptions,
+) -> Result<StartOutcome, ApiError> {
+    let began = Instant::now();
+    // Captured apart from `server.logs`, which only keeps what the log filter lets through.
+    *sync::lock(&server.startup_capture, "startup capture") = Some(VecDeque::new());
+    let started = start(app, state, server, options).await;
+    let captured = sync::lock(&server.startup_capture, "startup capture").take();
+    let captured_logs = || Vec::from(captured.unwrap_or_default());
+    match started {
+        Ok(result) => Ok(StartOutcome::Ready {
+            result,
+            elapsed_ms: began.elapsed().as_millis() as u64,
+        }),
+        Err(error @ ApiError::StartupTimeout { .. }) => Ok(StartOutcome::TimedOut {
+            error,
+            captured_logs: captured_logs(),
+        }),
+        Err(error @ ApiError::ExitedDuringStartup { code, .. }) => {
+            Ok(StartOutcome::CrashedDuringStartup {
+                exit_code: code,
+                error,
+                captured_logs: captured_logs(),
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Refuses lifecycle commands while attached to an external server.
+async fn ensure_managed(state: &ApiState) -> Result<(), ApiError> {
+    if state.config().await.is_external() {
+        return Err(ApiError::ExternalMode);
+    }
+    Ok(())
+}
 
-    Err("API server failed to start within 15 seconds".to_string())
+/// A user-initiated start, which also resets the automatic restart budget.
+async fn start<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &ApiState,
+    server: &ServerHandle,
+    options: LaunchOptions,
+) -> Result<StartResult, ApiError> {
+    ensure_managed(state).await?;
+    server.restart_count.store(0, Ordering::SeqCst);
+    *server.stats.lock().await = ServerStats::default();
+    server.reset_breaker().await;
+    server::launch(app, state, server, options).await
 }
 
+/// Starts the default instance in the background while the window loads, reporting
+/// failures as a server event instead of holding up app launch.
+async fn autostart(app: AppHandle) {
+    let state = app.state::<ApiState>();
+    let Ok(server) = state.instance(DEFAULT_INSTANCE).await else {
+        return;
+    };
+    let options = state.launch_options().await;
+    if let Err(e) = start(&app, &state, &server, options).await {
+        let message = e.to_string();
+        events::emit(&app, &server.name, ServerEvent::StartFailed { message });
+    }
+}
+
+#[tauri::command]
+async fn stop_api_server(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+    instance: Option<String>,
+) -> Result<StopResult, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    // A child left over from before switching to external mode is still ours to stop.
+    if server.child.lock().await.is_none() {
+        ensure_managed(&state).await?;
+    }
+    let exit = server::terminate(&app, &state, &server).await?;
+    Ok(StopResult::from_exit(exit))
+}
+
+/// Stops every instance, carrying on past failures, and reports each outcome.
 #[tauri::command]
-async fn stop_api_server(state: State<'_, ApiState>) -> Result<String, String> {
-    let mut child_lock = state.child.lock().map_err(|e| e.to_string())?;
-    if let Some(mut child) = child_lock.take() {
-        child.kill().map_err(|e| format!("Failed to kill: {}", e))?;
-        Ok("API server stopped".to_string())
+async fn stop_all(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+) -> Result<Vec<InstanceStop>, ApiError> {
+    Ok(stop_instances(&app, &state).await)
+}
+
+/// Stops the instances one by one, ordered by name. All of them are marked as stopping on
+/// purpose first, so none is revived by the watcher while it waits its turn.
+async fn stop_instances<R: Runtime>(app: &AppHandle<R>, state: &ApiState) -> Vec<InstanceStop> {
+    let mut instances = state.all_instances().await;
+    instances.sort_by(|a, b| a.name.cmp(&b.name));
+    for server in &instances {
+        server.intentional_stop.store(true, Ordering::SeqCst);
+    }
+    let mut stops = Vec::with_capacity(instances.len());
+    for server in &instances {
+        let outcome = server::terminate(app, state, server).await;
+        stops.push(InstanceStop {
+            instance: server.name.clone(),
+            result: outcome.as_ref().ok().copied().map(StopResult::from_exit),
+            error: outcome.err(),
+        });
+    }
+    stops
+}
+
+#[tauri::command]
+async fn restart_api_server(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+    instance: Option<String>,
+) -> Result<StartResult, ApiError> {
+    let server = state.instance(&instance_name(instance)).await?;
+    restart(&app, &state, &server).await
+}
+
+/// Aborts an in-progress start, killing the child if it was already spawned.
+#[tauri::command]
+async fn cancel_startup(
+    state: State<'_, ApiState>,
+    instance: Option<String>,
+) -> Result<String, ApiError> {
+    let server = state.existing(&instance_name(instance)).await?;
+    if server.cancel_startup().await {
+        Ok("API server startup cancelled".to_string())
     } else {
-        Ok("No API server running".to_string())
+        Ok("No API server startup in progress".to_string())
+    }
+}
+
+async fn restart<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &ApiState,
+    server: &ServerHandle,
+) -> Result<StartResult, ApiError> {
+    ensure_managed(state).await?;
+    if server::terminate(app, state, server).await?.is_some() {
+        // Give the OS a moment to release the port before rebinding it.
+        tokio::time::sleep(RESTART_GRACE).await;
+    }
+
+    server.restart_count.store(0, Ordering::SeqCst);
+    *server.stats.lock().await = ServerStats::default();
+    server.reset_breaker().await;
+    let result = server::launch(app, state, server, state.launch_options().await).await;
+    if result.is_err() {
+        server.set_status(ServerStatus::Stopped).await;
+    }
+    result
+}
+
+/// Switches an instance to a new port, restarting it there if it is running.
+#[tauri::command]
+async fn set_port(
+    app: AppHandle,
+    state: State<'_, ApiState>,
+    port: u16,
+    instance: Option<String>,
+) -> Result<String, ApiError> {
+    let config = state.config().await;
+    net::check_port(port, config.allow_privileged_port)?;
+    if config.metrics_port == Some(port) {
+        return Err(ApiError::InvalidPort {
+            port,
+            reason: "already used by the metrics endpoint".to_string(),
+        });
     }
+    let server = state.instance(&instance_name(instance)).await?;
+    *server.port.lock().await = port;
+    if *server.status.lock().await != ServerStatus::Running {
+        return Ok(format!("API server will use port {} on next start", port));
+    }
+    restart(&app, &state, &server)
+        .await
+        .map(|result| result.message)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    // Registered first so a second launch exits before its setup touches PID files or
+    // spawns anything, even while this instance is still starting its server.
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+        tray::show_main_window(app);
+    }));
+    #[cfg(desktop)]
+    let builder = builder.plugin(shortcut::plugin());
+    builder
         .plugin(tauri_plugin_shell::init())
-        .manage(ApiState {
-            port: 3838,
-            child: Mutex::new(None),
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_store::Builder::default().build())
+        .setup(|app| {
+            let config_path = app
+                .path()
+                .app_config_dir()
+                .ok()
+                .map(|dir| dir.join(config::CONFIG_FILE));
+            // A broken file must not take the app down; fall back to defaults and say why.
+            let (mut file_config, mut config_error) = match &config_path {
+                Some(path) => {
+                    let _ = config::write_default(path);
+                    match config::FileConfig::load(path) {
+                        Ok(file) => (file, None),
+                        Err(e) => (config::FileConfig::default(), Some(e)),
+                    }
+                }
+                None => (config::FileConfig::default(), None),
+            };
+            let shadowed = match settings::open(app.handle()) {
+                Some(store) => settings::StoreSettings::load(&store).overlay(&mut file_config),
+                None => Vec::new(),
+            };
+            let resource_dir = app.path().resource_dir().ok();
+            let mut config = Config::resolve(&file_config, resource_dir);
+            let log_dir = app.path().app_log_dir().ok();
+            let log_file = log_dir.as_deref().map(|dir| Arc::new(LogFile::new(dir)));
+            if let Some(guard) = logging::init(log_dir.as_deref(), &config.log_level) {
+                app.manage(guard);
+            }
+            if let Some(e) = &config_error {
+                tracing::warn!(error = %e, "failed to load settings; using defaults");
+            }
+            if !shadowed.is_empty() {
+                tracing::warn!(?shadowed, "settings store overrides values set in yep.toml");
+            }
+            if config.transport == Transport::UnixSocket && !config.uses_socket() {
+                tracing::warn!("Unix sockets aren't supported on this platform; using TCP");
+            }
+            if let Err(e) = net::check_port(config.port, config.allow_privileged_port) {
+                tracing::warn!(error = %e, "ignoring configured port");
+                config_error.get_or_insert_with(|| e.to_string());
+                config.port = config::DEFAULT_PORT;
+            }
+            let data_dir = app
+                .path()
+                .app_data_dir()
+                .ok()
+                .filter(|dir| std::fs::create_dir_all(dir).is_ok());
+            // A previous session may have crashed and left its servers running.
+            if let Some(dir) = &data_dir {
+                for pid in pidfile::kill_all_stale(dir) {
+                    tracing::info!(pid, "killed server left over from a previous session");
+                }
+            }
+            // Force-quit sessions leave no PID file, so optionally hunt them down by command line.
+            if config.orphan_sweep == OrphanSweep::AutoKill {
+                let pids: Vec<u32> = orphans::find(&config, &[])
+                    .iter()
+                    .map(|orphan| orphan.pid)
+                    .collect();
+                for pid in orphans::kill(&pids, &config, &[]) {
+                    tracing::info!(pid, "killed orphaned server");
+                }
+            }
+            let http = reqwest::Client::builder()
+                .connect_timeout(HTTP_CONNECT_TIMEOUT)
+                .timeout(HTTP_REQUEST_TIMEOUT)
+                .build()?;
+            let default_server = ServerHandle::new(
+                DEFAULT_INSTANCE.to_string(),
+                config.port,
+                config.restart_policy,
+                data_dir
+                    .as_deref()
+                    .map(|dir| pidfile::path_for(dir, DEFAULT_INSTANCE)),
+            );
+            let autostart_enabled = config.autostart && !config.is_external();
+            #[cfg(desktop)]
+            shortcut::register(app.handle(), &config.toggle_shortcut);
+            let health_monitor = AtomicBool::new(config.health_monitor);
+            let watch_config = config.watch_config;
+            let metrics_port = config.metrics_port;
+            app.manage(ApiState {
+                config: RwLock::new(Arc::new(config)),
+                config_path,
+                config_error: Mutex::new(config_error),
+                data_dir,
+                log_subscribers: LogSubscribers::default(),
+                log_filter: SharedLogFilter::default(),
+                log_file: log_file.clone(),
+                http,
+                instances: Mutex::new(HashMap::from([(
+                    DEFAULT_INSTANCE.to_string(),
+                    Arc::new(default_server),
+                )])),
+                persistent_token: Mutex::new(None),
+                health_monitor,
+                spawner: Arc::new(OsSpawner),
+                shutting_down: AtomicBool::new(false),
+                config_watcher: std::sync::Mutex::new(None),
+                metrics_server: std::sync::Mutex::new(None),
+            });
+            tauri::async_runtime::spawn(watcher::watch(app.handle().clone()));
+            if watch_config {
+                app.state::<ApiState>().watch_config(app.handle(), true);
+            }
+            if metrics_port.is_some() {
+                app.state::<ApiState>()
+                    .serve_metrics(app.handle(), metrics_port);
+            }
+            if let Some(file) = log_file {
+                tauri::async_runtime::spawn(async move {
+                    loop {
+                        tokio::time::sleep(logfile::FLUSH_INTERVAL).await;
+                        file.flush();
+                    }
+                });
+            }
+            tray::init(app.handle())?;
+            notifications::init(app.handle());
+            settings::watch(app.handle());
+            if autostart_enabled {
+                tauri::async_runtime::spawn(autostart(app.handle().clone()));
+            }
+            Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_api_info,
+            open_api_in_browser,
+            get_server_status,
+            get_all_instances,
+            get_server_stats,
+            get_health_stats,
+            get_or_create_api_token,
+            rotate_api_token,
+            start_health_monitor,
+            stop_health_monitor,
+            api_request,
+            probe_server,
+            get_server_version,
             start_api_server,
+            start_api_server_detailed,
             stop_api_server,
+            stop_all,
+            restart_api_server,
+            set_port,
+            cancel_startup,
+            check_bun,
+            collect_diagnostics,
+            find_orphans,
+            kill_orphans,
+            set_restart_policy,
+            get_config,
+            get_last_error,
+            clear_last_error,
+            set_config,
+            set_config_and_restart,
+            reload_config,
+            get_logs,
+            get_logs_page,
+            get_metrics_text,
+            set_log_filter,
+            clear_logs,
+            get_log_file_path,
+            subscribe_logs,
+            unsubscribe_logs,
         ])
         .on_window_event(|window, event| {
-            if let tauri::WindowEvent::Destroyed = event {
-                if let Some(state) = window.try_state::<ApiState>() {
-                    if let Ok(mut child_lock) = state.child.lock() {
-                        if let Some(mut child) = child_lock.take() {
-                            let _ = child.kill();
-                        }
-                    }
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                let Some(state) = window.try_state::<ApiState>() else {
+                    return;
+                };
+                // Window events run on the main thread, outside the async runtime.
+                if state.config.blocking_read().close_to_tray {
+                    api.prevent_close();
+                    let _ = window.hide();
                 }
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            tauri::RunEvent::ExitRequested { api, code, .. } => {
+                let Some(state) = app.try_state::<ApiState>() else {
+                    return;
+                };
+                // The last window closing isn't a quit when the servers live on in the tray.
+                if code.is_none() && state.config.blocking_read().close_to_tray {
+                    api.prevent_exit();
+                    return;
+                }
+                // The second request comes from `shutdown` itself, once the servers are down.
+                if state.shutting_down.swap(true, Ordering::SeqCst) {
+                    return;
+                }
+                api.prevent_exit();
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    shutdown(&app).await;
+                    app.exit(code.unwrap_or(0));
+                });
+            }
+            // Last resort for anything the graceful shutdown didn't get to.
+            tauri::RunEvent::Exit => {
+                if let Some(state) = app.try_state::<ApiState>() {
+                    state.shutting_down.store(true, Ordering::SeqCst);
+                    sync::lock(&state.config_watcher, "config watcher").take();
+                    if let Some(server) = sync::lock(&state.metrics_server, "metrics server").take()
+                    {
+                        server.abort();
+                    }
+                    for server in state.instances.blocking_lock().values() {
+                        server::kill_blocking(server);
+                    }
+                    if let Some(file) = &state.log_file {
+                        file.flush();
+                    }
+                }
+            }
+            _ => {}
+        });
+}
+
+/// Stops every server gracefully, so exiting never leaves an orphan or a PID file behind.
+async fn shutdown<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<ApiState>();
+    for stop in stop_instances(app, &state).await {
+        if let Some(e) = stop.error {
+            tracing::warn!(instance = %stop.instance, error = %e, "failed to stop server on exit");
+        }
+    }
 }
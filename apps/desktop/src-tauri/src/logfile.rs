@@ -0,0 +1,112 @@
+use crate::logs::{LogLine, LogStream};
+use crate::sync;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub const LOG_FILE: &str = "api-server.log";
+/// Size at which the active file is rotated out.
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Files kept in total, counting the active one.
+const MAX_FILES: usize = 5;
+/// How often buffered lines are pushed to disk.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Server output persisted across app restarts, rotated by size as
+/// `api-server.log`, `api-server.1.log`, ... `api-server.4.log`.
+///
+/// Every reader thread writes through the same lock, so rotation never races a write.
+pub struct LogFile {
+    path: PathBuf,
+    writer: Mutex<Option<Writer>>,
+}
+
+struct Writer {
+    file: BufWriter<File>,
+    len: u64,
+}
+
+impl LogFile {
+    pub fn new(dir: &Path) -> Self {
+        LogFile {
+            path: dir.join(LOG_FILE),
+            writer: Mutex::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Buffers one line. Failures are dropped: losing a disk log line must never stall
+    /// the pipe reader.
+    pub fn append(&self, line: &LogLine) {
+        let mut writer = sync::lock(&self.writer, "log file");
+        let _ = self.append_locked(&mut writer, line);
+    }
+
+    pub fn flush(&self) {
+        if let Some(writer) = sync::lock(&self.writer, "log file").as_mut() {
+            let _ = writer.file.flush();
+        }
+    }
+
+    fn append_locked(&self, writer: &mut Option<Writer>, line: &LogLine) -> io::Result<()> {
+        if writer.is_none() {
+            *writer = Some(self.open()?);
+        }
+        let Some(active) = writer.as_mut() else {
+            return Ok(());
+        };
+        let stream = match line.stream {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+            LogStream::System => "system",
+        };
+        let entry = format!(
+            "{} [{}] {}: {}\n",
+            line.ts, line.instance, stream, line.line
+        );
+        active.file.write_all(entry.as_bytes())?;
+        active.len += entry.len() as u64;
+        if active.len >= MAX_FILE_BYTES {
+            active.file.flush()?;
+            *writer = None;
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    fn open(&self) -> io::Result<Writer> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let len = file.metadata()?.len();
+        Ok(Writer {
+            file: BufWriter::new(file),
+            len,
+        })
+    }
+
+    /// Shifts every file up one slot, dropping the oldest. The next append reopens a
+    /// fresh active file.
+    fn rotate(&self) -> io::Result<()> {
+        for index in (1..MAX_FILES - 1).rev() {
+            let from = self.numbered(index);
+            if from.exists() {
+                std::fs::rename(&from, self.numbered(index + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.numbered(1))
+    }
+
+    fn numbered(&self, index: usize) -> PathBuf {
+        self.path.with_extension(format!("{}.log", index))
+    }
+}
@@ -0,0 +1,81 @@
+use std::path::Path;
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+use tracing_subscriber::EnvFilter;
+
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+const LOG_FILE_PREFIX: &str = "yep-desktop";
+const LOG_FILE_SUFFIX: &str = "log";
+/// Daily files kept in the log dir before the oldest is deleted.
+const MAX_LOG_FILES: usize = 7;
+
+type Reload = Box<dyn Fn(EnvFilter) -> Result<(), String> + Send + Sync>;
+
+/// Swaps the filter of the subscriber `init` installed.
+static RELOAD: OnceLock<Reload> = OnceLock::new();
+
+/// Whether `level` parses as a filter, e.g. `debug` or `yep_desktop_lib=trace,info`.
+pub fn is_valid_level(level: &str) -> bool {
+    EnvFilter::try_new(level).is_ok()
+}
+
+/// Sends the app's own diagnostics to stderr and, when `log_dir` is usable, to a daily
+/// rotating file there. `RUST_LOG` takes precedence over the configured `level`.
+/// The returned guard must be kept alive; dropping it flushes and stops file logging.
+pub fn init(log_dir: Option<&Path>, level: &str) -> Option<WorkerGuard> {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(level))
+        .unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_LEVEL));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    let file = log_dir.and_then(|dir| {
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix(LOG_FILE_SUFFIX)
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)
+            .ok()
+    });
+    match file {
+        Some(file) => {
+            let (file, guard) = tracing_appender::non_blocking(file);
+            let builder = builder
+                .with_ansi(false)
+                .with_writer(std::io::stderr.and(file))
+                .with_filter_reloading();
+            let handle = builder.reload_handle();
+            if builder.try_init().is_ok() {
+                let _ = RELOAD.set(Box::new(move |filter| {
+                    handle.reload(filter).map_err(|e| e.to_string())
+                }));
+            }
+            Some(guard)
+        }
+        None => {
+            let builder = builder.with_writer(std::io::stderr).with_filter_reloading();
+            let handle = builder.reload_handle();
+            if builder.try_init().is_ok() {
+                let _ = RELOAD.set(Box::new(move |filter| {
+                    handle.reload(filter).map_err(|e| e.to_string())
+                }));
+            }
+            None
+        }
+    }
+}
+
+/// Applies a new configured `level` to the running subscriber. Does nothing while
+/// `RUST_LOG` is set, since it takes precedence, or before `init`.
+pub fn set_level(level: &str) -> Result<(), String> {
+    if std::env::var_os(EnvFilter::DEFAULT_ENV).is_some() {
+        return Ok(());
+    }
+    let filter = EnvFilter::try_new(level).map_err(|e| e.to_string())?;
+    match RELOAD.get() {
+        Some(reload) => reload(filter),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,336 @@
+use crate::logfile::LogFile;
+use crate::{sync, token};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::ipc::Channel;
+use tokio::sync::oneshot;
+
+pub const STDERR_TAIL_LINES: usize = 50;
+pub const LOG_BUFFER_LINES: usize = 1000;
+/// How many leading words of a line may hold its level, past e.g. a timestamp.
+const LEVEL_WORDS: usize = 8;
+
+/// Lines captured by the pipe reader threads, which run outside the async runtime.
+pub type LineBuffer = Arc<Mutex<VecDeque<String>>>;
+
+/// Every line of a startup in progress, including those the display filter drops, so a
+/// failed start can report its full output. `None` while nothing is being captured.
+pub type StartupCapture = Arc<Mutex<Option<VecDeque<LogLine>>>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+    /// Written by the app itself, e.g. the command it spawned.
+    System,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LogLine {
+    /// Name of the server instance that produced the line.
+    pub instance: String,
+    pub stream: LogStream,
+    pub line: String,
+    /// Milliseconds since the Unix epoch.
+    pub ts: u64,
+    /// Position in the instance's output, counting from 0. Assigned by `LogBuffer::push`
+    /// and never reused, not even after a clear, so it can anchor pagination.
+    pub seq: u64,
+}
+
+/// Severity of a server output line, as far as it can be told from the text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    #[serde(alias = "warning")]
+    Warn,
+    #[serde(alias = "fatal")]
+    Error,
+}
+
+impl LogLevel {
+    fn parse(word: &str) -> Option<Self> {
+        match word.to_ascii_lowercase().as_str() {
+            "trace" | "trc" => Some(LogLevel::Trace),
+            "debug" | "dbg" | "verbose" => Some(LogLevel::Debug),
+            "info" | "inf" => Some(LogLevel::Info),
+            "warn" | "warning" | "wrn" => Some(LogLevel::Warn),
+            "error" | "err" | "fatal" | "panic" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+
+    /// Reads the level off `line`: from `pattern`'s `level` group or first capture when
+    /// given, otherwise from the first level name among its leading words, which covers
+    /// `[WARN] ...`, `error: ...`, `level=debug` and JSON loggers. Anything else is info.
+    pub fn detect(line: &str, pattern: Option<&Regex>) -> Self {
+        let level = match pattern {
+            Some(pattern) => pattern
+                .captures(line)
+                .and_then(|captures| captures.name("level").or_else(|| captures.get(1)))
+                .and_then(|level| LogLevel::parse(level.as_str())),
+            None => line
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .take(LEVEL_WORDS)
+                .find_map(LogLevel::parse),
+        };
+        level.unwrap_or(LogLevel::Info)
+    }
+}
+
+/// Which server output reaches the log buffer and the subscribers, set through
+/// `set_log_filter`. The log file always gets everything, as do the app's own lines.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LogFilter {
+    /// Only these streams, or all of them when `None`.
+    pub streams: Option<Vec<LogStream>>,
+    pub min_level: Option<LogLevel>,
+}
+
+impl LogFilter {
+    fn allows(&self, stream: LogStream, line: &str, pattern: Option<&Regex>) -> bool {
+        if matches!(stream, LogStream::System) {
+            return true;
+        }
+        if let Some(streams) = &self.streams {
+            if !streams.contains(&stream) {
+                return false;
+            }
+        }
+        self.min_level
+            .is_none_or(|min| LogLevel::detect(line, pattern) >= min)
+    }
+}
+
+/// The filter in effect, consulted by every reader thread for each line.
+pub type SharedLogFilter = Arc<Mutex<LogFilter>>;
+
+/// One slice of an instance's output, see `LogBuffer::page`.
+#[derive(Clone, Debug, Serialize)]
+pub struct LogPage {
+    pub lines: Vec<LogLine>,
+    /// Lines written so far, which is also the `seq` the next one will get.
+    pub total: u64,
+    /// `seq` of the oldest line still buffered; anything before it has been dropped.
+    pub oldest: u64,
+    /// More lines follow this page.
+    pub has_more: bool,
+}
+
+#[derive(Default)]
+struct Lines {
+    lines: VecDeque<LogLine>,
+    next_seq: u64,
+}
+
+/// Bounded buffer of the most recent child output, shared with the reader threads.
+#[derive(Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<Lines>>);
+
+impl LogBuffer {
+    /// Numbers `line` and stores it, returning it as stored.
+    pub fn push(&self, mut line: LogLine) -> LogLine {
+        let mut buffer = sync::lock(&self.0, "log buffer");
+        line.seq = buffer.next_seq;
+        buffer.next_seq += 1;
+        if buffer.lines.len() == LOG_BUFFER_LINES {
+            buffer.lines.pop_front();
+        }
+        buffer.lines.push_back(line.clone());
+        line
+    }
+
+    /// Returns up to `limit` of the most recent lines, oldest first.
+    pub fn recent(&self, limit: Option<usize>) -> Vec<LogLine> {
+        let buffer = sync::lock(&self.0, "log buffer");
+        let skip = limit.map_or(0, |limit| buffer.lines.len().saturating_sub(limit));
+        buffer.lines.iter().skip(skip).cloned().collect()
+    }
+
+    /// Returns up to `limit` lines starting at sequence number `offset`, oldest first.
+    /// Lines appended meanwhile don't shift a page; an offset that has already been
+    /// dropped starts at the oldest line left.
+    pub fn page(&self, offset: u64, limit: usize) -> LogPage {
+        let buffer = sync::lock(&self.0, "log buffer");
+        let oldest = buffer
+            .lines
+            .front()
+            .map_or(buffer.next_seq, |line| line.seq);
+        let skip = offset.saturating_sub(oldest) as usize;
+        let lines: Vec<LogLine> = buffer
+            .lines
+            .iter()
+            .skip(skip)
+            .take(limit)
+            .cloned()
+            .collect();
+        let end = lines.last().map_or(offset.max(oldest), |line| line.seq + 1);
+        LogPage {
+            has_more: end < buffer.next_seq,
+            total: buffer.next_seq,
+            oldest,
+            lines,
+        }
+    }
+
+    pub fn clear(&self) {
+        sync::lock(&self.0, "log buffer").lines.clear();
+    }
+}
+
+struct Subscriber {
+    id: u32,
+    /// Only lines from this instance, or every instance when `None`.
+    instance: Option<String>,
+    channel: Channel<LogLine>,
+}
+
+/// Frontend components streaming live output, each over its own IPC channel.
+#[derive(Clone, Default)]
+pub struct LogSubscribers {
+    next_id: Arc<AtomicU32>,
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+impl LogSubscribers {
+    pub fn subscribe(&self, instance: Option<String>, channel: Channel<LogLine>) -> u32 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        sync::lock(&self.subscribers, "log subscribers").push(Subscriber {
+            id,
+            instance,
+            channel,
+        });
+        id
+    }
+
+    /// Returns whether `id` was subscribed.
+    pub fn unsubscribe(&self, id: u32) -> bool {
+        let mut subscribers = sync::lock(&self.subscribers, "log subscribers");
+        let before = subscribers.len();
+        subscribers.retain(|subscriber| subscriber.id != id);
+        subscribers.len() != before
+    }
+
+    /// Sends `line` to every interested subscriber, dropping any whose channel is gone.
+    fn publish(&self, line: &LogLine) {
+        sync::lock(&self.subscribers, "log subscribers").retain(|subscriber| {
+            let wanted = subscriber
+                .instance
+                .as_ref()
+                .is_none_or(|instance| *instance == line.instance);
+            !wanted || subscriber.channel.send(line.clone()).is_ok()
+        });
+    }
+}
+
+/// Where a reader thread delivers the lines it reads.
+#[derive(Clone)]
+pub struct LogSink {
+    pub instance: String,
+    pub subscribers: LogSubscribers,
+    pub logs: LogBuffer,
+    pub file: Option<Arc<LogFile>>,
+    /// Secret masked out of every line before it is stored or streamed.
+    pub redact: Option<String>,
+    pub filter: SharedLogFilter,
+    /// See `Config::output_level_pattern`.
+    pub level_pattern: Option<Regex>,
+    pub capture: StartupCapture,
+}
+
+impl LogSink {
+    /// Records `line` in the log file and any startup capture and, unless the filter drops
+    /// it, in the buffer, and streams it to the subscribers.
+    pub fn write(&self, stream: LogStream, line: String) {
+        let entry = LogLine {
+            instance: self.instance.clone(),
+            stream,
+            line: token::redact(&line, self.redact.as_deref()),
+            ts: now_millis(),
+            seq: 0,
+        };
+        if let Some(file) = &self.file {
+            file.append(&entry);
+        }
+        let allowed = sync::lock(&self.filter, "log filter").allows(
+            stream,
+            &entry.line,
+            self.level_pattern.as_ref(),
+        );
+        let entry = if allowed {
+            let entry = self.logs.push(entry);
+            self.subscribers.publish(&entry);
+            entry
+        } else {
+            entry
+        };
+        if let Some(lines) = sync::lock(&self.capture, "startup capture").as_mut() {
+            if lines.len() == LOG_BUFFER_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(entry);
+        }
+    }
+}
+
+/// Fires `ready` the first time a line containing `pattern` is read.
+pub struct Banner {
+    pub pattern: String,
+    pub ready: oneshot::Sender<()>,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Continuously drains a child pipe so the child never blocks on a full pipe buffer.
+/// Each line is streamed to the log subscribers and recorded in the sink (and its log
+/// file); when a tail buffer is given, the most recent lines are also kept in it, and
+/// when a banner is given it is signalled once its pattern shows up. The thread ends when
+/// the child closes the pipe.
+pub fn spawn_reader<R: Read + Send + 'static>(
+    pipe: R,
+    stream: LogStream,
+    sink: LogSink,
+    tail: Option<LineBuffer>,
+    mut banner: Option<Banner>,
+) {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(pipe);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let line = String::from_utf8_lossy(&buf).trim_end().to_string();
+            if banner.as_ref().is_some_and(|b| line.contains(&b.pattern)) {
+                if let Some(banner) = banner.take() {
+                    let _ = banner.ready.send(());
+                }
+            }
+            if let Some(tail) = &tail {
+                let mut tail = sync::lock(tail, "stderr tail");
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line.clone());
+            }
+            sink.write(stream, line);
+        }
+    });
+}
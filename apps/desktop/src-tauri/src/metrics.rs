@@ -0,0 +1,191 @@
+use crate::server::ServerStatus;
+use crate::ApiState;
+use std::fmt::Write as _;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Largest request head the endpoint reads before answering anyway.
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+/// How long a scraper gets to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Pause after a failed accept before trying again.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Lifetime totals for one instance. Unlike `ServerStats` they are never reset, as
+/// Prometheus expects of a counter.
+#[derive(Default)]
+pub struct Counters {
+    pub spawns: AtomicU64,
+    pub crashes: AtomicU64,
+    pub restarts: AtomicU64,
+    pub probes: AtomicU64,
+    pub probe_failures: AtomicU64,
+}
+
+struct Sample {
+    instance: String,
+    spawns: u64,
+    crashes: u64,
+    restarts: u64,
+    probes: u64,
+    probe_failures: u64,
+    up: bool,
+    uptime_secs: f64,
+}
+
+/// The supervisor's own counters in the Prometheus text format, one series per instance.
+pub async fn render(state: &ApiState) -> String {
+    let mut samples = Vec::new();
+    for server in state.all_instances().await {
+        let up = *server.status.lock().await == ServerStatus::Running;
+        let uptime = server.uptime().await.unwrap_or_default();
+        let counters = &server.counters;
+        samples.push(Sample {
+            instance: escape(&server.name),
+            spawns: counters.spawns.load(Ordering::Relaxed),
+            crashes: counters.crashes.load(Ordering::Relaxed),
+            restarts: counters.restarts.load(Ordering::Relaxed),
+            probes: counters.probes.load(Ordering::Relaxed),
+            probe_failures: counters.probe_failures.load(Ordering::Relaxed),
+            up,
+            uptime_secs: uptime.as_secs_f64(),
+        });
+    }
+    samples.sort_by(|a, b| a.instance.cmp(&b.instance));
+
+    let mut text = String::new();
+    let mut family =
+        |name: &str, kind: &str, help: &str, value: &dyn Fn(&Sample) -> Option<f64>| {
+            let _ = writeln!(text, "# HELP yep_{} {}", name, help);
+            let _ = writeln!(text, "# TYPE yep_{} {}", name, kind);
+            for sample in &samples {
+                if let Some(value) = value(sample) {
+                    let _ = writeln!(
+                        text,
+                        "yep_{}{{instance=\"{}\"}} {}",
+                        name, sample.instance, value
+                    );
+                }
+            }
+        };
+    family(
+        "spawns_total",
+        "counter",
+        "Server processes spawned.",
+        &|s| Some(s.spawns as f64),
+    );
+    family(
+        "crashes_total",
+        "counter",
+        "Unexpected server exits.",
+        &|s| Some(s.crashes as f64),
+    );
+    family(
+        "restarts_total",
+        "counter",
+        "Automatic restarts after a crash or hang.",
+        &|s| Some(s.restarts as f64),
+    );
+    family(
+        "health_probes_total",
+        "counter",
+        "Health monitor probes sent.",
+        &|s| Some(s.probes as f64),
+    );
+    family(
+        "health_probe_failures_total",
+        "counter",
+        "Health monitor probes that failed.",
+        &|s| Some(s.probe_failures as f64),
+    );
+    family(
+        "health_probe_success_ratio",
+        "gauge",
+        "Share of health monitor probes that passed, over the app's lifetime.",
+        &|s| (s.probes > 0).then(|| 1.0 - s.probe_failures as f64 / s.probes as f64),
+    );
+    family("up", "gauge", "Whether the server is running.", &|s| {
+        Some(if s.up { 1.0 } else { 0.0 })
+    });
+    family(
+        "uptime_seconds",
+        "gauge",
+        "How long the current server process has run.",
+        &|s| Some(s.uptime_secs),
+    );
+    text
+}
+
+/// Label values are quoted, so quotes, backslashes and newlines need escaping.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serves `render` at `/metrics` on the loopback interface until the task is aborted.
+pub async fn serve(app: AppHandle, port: u16) {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::warn!(port, error = %e, "failed to bind the metrics endpoint");
+            return;
+        }
+    };
+    tracing::info!(%addr, "serving metrics");
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                // Usually out of file descriptors; retrying at once would just spin.
+                tracing::warn!(error = %e, "metrics endpoint failed to accept a connection");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = respond(&app, stream).await {
+                tracing::debug!(error = %e, "metrics request failed");
+            }
+        });
+    }
+}
+
+async fn respond(app: &AppHandle, mut stream: TcpStream) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|end| end == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let read = tokio::time::timeout(READ_TIMEOUT, stream.read(&mut buf))
+            .await
+            .map_err(|_| std::io::ErrorKind::TimedOut)??;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut line = request.lines().next().unwrap_or_default().split(' ');
+    let (method, path) = (line.next(), line.next());
+    let (status, body) = match (method, path) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", render(&app.state::<ApiState>()).await),
+        (Some("GET"), _) => ("404 Not Found", String::new()),
+        _ => ("405 Method Not Allowed", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        CONTENT_TYPE,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
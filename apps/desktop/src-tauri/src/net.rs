@@ -0,0 +1,155 @@
+use crate::error::ApiError;
+use serde::Serialize;
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv6Addr, TcpListener};
+use std::path::{Path, PathBuf};
+
+/// Ports below this need elevated privileges on most systems.
+pub const MIN_UNPRIVILEGED_PORT: u16 = 1024;
+
+/// Rejects ports below 1024 unless `allow_privileged` is set. Allowed privileged ports
+/// are logged, since binding one usually fails without root. Port 0 is always accepted and
+/// means "let the OS pick", see `ephemeral_port`.
+pub fn check_port(port: u16, allow_privileged: bool) -> Result<(), ApiError> {
+    if (1..MIN_UNPRIVILEGED_PORT).contains(&port) {
+        if !allow_privileged {
+            return Err(ApiError::InvalidPort {
+                port,
+                reason: "ports below 1024 need elevated privileges; \
+                         set allow_privileged_port to use one"
+                    .to_string(),
+            });
+        }
+        tracing::warn!(
+            port,
+            "using a privileged port; binding may fail without elevated privileges"
+        );
+    }
+    Ok(())
+}
+
+/// How far above the preferred port to look for a free one.
+const PORT_SCAN_RANGE: u16 = 100;
+
+/// Whether `host` is an IP literal or a syntactically valid DNS hostname.
+pub fn is_valid_host(host: &str) -> bool {
+    if host.parse::<IpAddr>().is_ok() {
+        return true;
+    }
+    host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Whether binding to `host` listens on every interface.
+pub fn is_wildcard(host: &str) -> bool {
+    host.parse::<IpAddr>().is_ok_and(|ip| ip.is_unspecified())
+}
+
+/// Who can connect to a server, judging by the host it binds to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Exposure {
+    /// Only this machine: a loopback address, `localhost`, or a Unix socket.
+    Loopback,
+    /// Every network the machine is on: `0.0.0.0` or `::`.
+    AllInterfaces,
+    /// A specific address or hostname, usually one other devices can reach too.
+    Custom(String),
+}
+
+impl Exposure {
+    pub fn of(host: &str) -> Self {
+        match host.parse::<IpAddr>() {
+            Ok(ip) if ip.is_loopback() => Exposure::Loopback,
+            Ok(ip) if ip.is_unspecified() => Exposure::AllInterfaces,
+            _ if host.eq_ignore_ascii_case("localhost") => Exposure::Loopback,
+            _ => Exposure::Custom(host.to_string()),
+        }
+    }
+
+    pub fn externally_reachable(&self) -> bool {
+        *self != Exposure::Loopback
+    }
+}
+
+/// Address to reach a server bound to `host`; wildcard binds are reached over loopback.
+pub fn connect_host(host: &str) -> &str {
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) if ip.is_unspecified() => "127.0.0.1",
+        Ok(IpAddr::V6(ip)) if ip.is_unspecified() => "::1",
+        _ => host,
+    }
+}
+
+/// `host:port` as it appears in a URL, bracketing IPv6 literals (`[::1]:3838`).
+pub fn authority(host: &str, port: u16) -> String {
+    if host.parse::<Ipv6Addr>().is_ok() {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// A port the OS considers free on `host`, found by binding port 0 and reading back what
+/// was assigned. The listener is closed before the server binds the port, so another
+/// process could grab it in between; that window is small enough for throwaway instances.
+pub fn ephemeral_port(host: &str) -> Result<u16, ApiError> {
+    TcpListener::bind((host, 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| ApiError::PortUnavailable(format!("Failed to get a free port: {}", e)))
+}
+
+/// Returns `preferred` if it can be bound on `host`, otherwise the first free port above it.
+pub fn find_free_port(host: &str, preferred: u16) -> Result<u16, ApiError> {
+    let last = preferred.saturating_add(PORT_SCAN_RANGE);
+    for port in preferred..=last {
+        match TcpListener::bind((host, port)) {
+            Ok(_) => return Ok(port),
+            Err(e) if e.kind() == ErrorKind::AddrInUse => continue,
+            Err(e) => {
+                return Err(ApiError::PortUnavailable(format!(
+                    "Failed to bind port {}: {}",
+                    port, e
+                )))
+            }
+        }
+    }
+    Err(ApiError::PortUnavailable(format!(
+        "No free port in range {}..={}",
+        preferred, last
+    )))
+}
+
+/// Where a running server can be reached.
+#[derive(Clone, Debug)]
+pub enum Endpoint {
+    Tcp { host: String, port: u16 },
+    Unix(PathBuf),
+}
+
+/// Gets `path` ready for the server to bind: creates its directory, private to the current
+/// user on Unix, and removes a socket left behind by a server that didn't clean up.
+pub fn prepare_socket(path: &Path) -> Result<(), ApiError> {
+    let failed =
+        |e: std::io::Error| ApiError::SocketUnavailable(format!("{}: {}", path.display(), e));
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(failed)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+                .map_err(failed)?;
+        }
+    }
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(failed(e)),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,31 @@
+use crate::events::{self, ServerEvent};
+use crate::{ApiState, DEFAULT_INSTANCE};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+const TITLE: &str = "yep";
+
+/// Shows a desktop notification when a server becomes ready or crashes, unless
+/// notifications are turned off in the config.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    events::listen(app, move |instance, event| {
+        let body = match event {
+            ServerEvent::Ready { port } => format!("API ready on port {}", port),
+            ServerEvent::Crashed { .. } => "API server stopped unexpectedly".to_string(),
+            _ => return,
+        };
+        let app = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if !app.state::<ApiState>().config().await.notifications {
+                return;
+            }
+            let title = if instance == DEFAULT_INSTANCE {
+                TITLE.to_string()
+            } else {
+                format!("{} ({})", TITLE, instance)
+            };
+            let _ = app.notification().builder().title(title).body(body).show();
+        });
+    });
+}
@@ -0,0 +1,117 @@
+use crate::config::{Config, LaunchMode, DEFAULT_SCRIPT};
+use regex::Regex;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::Path;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+/// Pause between attempts when killing an orphan fails.
+const KILL_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// A bun API server that looks like ours but isn't managed by this app session.
+#[derive(Clone, Serialize)]
+pub struct Orphan {
+    pub pid: u32,
+    pub command: String,
+    /// Unix timestamp (seconds) reported by the OS.
+    pub started_at: u64,
+}
+
+/// Lists leftover servers, skipping anything in `managed` and anything this process
+/// spawned. A server matches `config.process_match` when that is set, and otherwise looks
+/// like one started the way `config.launch_mode` starts them.
+pub fn find(config: &Config, managed: &[u32]) -> Vec<Orphan> {
+    // Checked by `Config::validate`.
+    let pattern = config
+        .process_match
+        .as_deref()
+        .and_then(|pattern| Regex::new(pattern).ok());
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_cmd(UpdateKind::Always)
+            .with_exe(UpdateKind::Always),
+    );
+    let own = Pid::from_u32(std::process::id());
+    system
+        .processes()
+        .iter()
+        .filter(|(pid, process)| {
+            pid.as_u32() != own.as_u32()
+                && process.parent() != Some(own)
+                && !managed.contains(&pid.as_u32())
+                && match (&config.launch_mode, &pattern) {
+                    // The external server looks just like an orphan but isn't ours to kill.
+                    (LaunchMode::External, _) => false,
+                    (_, Some(pattern)) => pattern.is_match(&join_cmd(process.cmd())),
+                    (LaunchMode::BunScript, None) => {
+                        is_bun_server(process.name(), process.cmd(), &config.script_path)
+                    }
+                    (LaunchMode::Binary { path, .. }, None) => {
+                        process.exe() == Some(path.as_path())
+                    }
+                }
+        })
+        .map(|(pid, process)| Orphan {
+            pid: pid.as_u32(),
+            command: join_cmd(process.cmd()),
+            started_at: process.start_time(),
+        })
+        .collect()
+}
+
+/// Kills the orphans among `pids`, re-checking each one so a PID that was reused since it
+/// was listed is left alone, and retrying a failed kill `config.kill_retries` times.
+/// Returns the PIDs that were killed.
+pub fn kill(pids: &[u32], config: &Config, managed: &[u32]) -> Vec<u32> {
+    let orphans = find(config, managed);
+    let mut system = System::new();
+    orphans
+        .into_iter()
+        .filter(|orphan| pids.contains(&orphan.pid))
+        .filter(|orphan| {
+            let pid = Pid::from_u32(orphan.pid);
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&[pid]),
+                true,
+                ProcessRefreshKind::nothing(),
+            );
+            system.process(pid).is_some_and(|process| {
+                process.start_time() == orphan.started_at
+                    && (0..=config.kill_retries).any(|attempt| {
+                        if attempt > 0 {
+                            std::thread::sleep(KILL_RETRY_DELAY);
+                        }
+                        process.kill()
+                    })
+            })
+        })
+        .map(|orphan| orphan.pid)
+        .collect()
+}
+
+/// Matches the bun executable running our entry script with the `api` subcommand, so
+/// unrelated bun processes are never touched.
+fn is_bun_server(name: &std::ffi::OsStr, cmd: &[OsString], script_path: &Path) -> bool {
+    let is_bun = Path::new(name)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("bun"));
+    if !is_bun {
+        return false;
+    }
+    cmd.windows(2).any(|pair| {
+        let script = Path::new(&pair[0]);
+        (script == script_path || script.ends_with(DEFAULT_SCRIPT)) && pair[1] == "api"
+    })
+}
+
+fn join_cmd(cmd: &[OsString]) -> String {
+    cmd.iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+pub const PID_FILE: &str = "api-server.pid";
+const PID_FILE_PREFIX: &str = "api-server";
+const PID_FILE_EXT: &str = "pid";
+
+/// Allowed skew between our recorded spawn time and the OS-reported process start time.
+const START_TIME_TOLERANCE_SECS: u64 = 5;
+
+/// The managed child as recorded on disk, so a later app launch can find an orphan.
+#[derive(Serialize, Deserialize)]
+pub struct PidRecord {
+    pub pid: u32,
+    /// Unix timestamp (seconds) of the spawn, used to guard against PID reuse.
+    pub started_at: u64,
+    /// The command line as the OS reported it right after the spawn, a second guard
+    /// against PID reuse. Empty in files written before it was recorded.
+    #[serde(default)]
+    pub command: Vec<String>,
+}
+
+impl PidRecord {
+    pub fn now(pid: u32) -> Self {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut system = System::new();
+        let command = refresh(&mut system, pid)
+            .map(command_of)
+            .unwrap_or_default();
+        PidRecord {
+            pid,
+            started_at,
+            command,
+        }
+    }
+
+    /// Why `process` can't be the one this record describes, if it can't.
+    fn mismatch(&self, process: &Process) -> Option<&'static str> {
+        if process.start_time().abs_diff(self.started_at) > START_TIME_TOLERANCE_SECS {
+            return Some("start time differs");
+        }
+        if !self.command.is_empty() && command_of(process) != self.command {
+            return Some("command line differs");
+        }
+        None
+    }
+}
+
+fn refresh(system: &mut System, pid: u32) -> Option<&Process> {
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_cmd(UpdateKind::Always),
+    );
+    system.process(pid)
+}
+
+fn command_of(process: &Process) -> Vec<String> {
+    process
+        .cmd()
+        .iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// PID file for a named instance; the default instance keeps the original file name.
+pub fn path_for(dir: &Path, instance: &str) -> PathBuf {
+    if instance == crate::DEFAULT_INSTANCE {
+        dir.join(PID_FILE)
+    } else {
+        dir.join(format!("{}-{}.{}", PID_FILE_PREFIX, instance, PID_FILE_EXT))
+    }
+}
+
+pub fn write(path: &Path, record: &PidRecord) -> std::io::Result<()> {
+    let json = serde_json::to_string(record).map_err(std::io::Error::other)?;
+    std::fs::write(path, json)
+}
+
+pub fn remove(path: &Path) {
+    let _ = std::fs::remove_file(path);
+}
+
+/// Kills the process recorded in a stale PID file if it is still alive and is the same
+/// process we spawned, judged by its start time and command line, then removes the file.
+/// On Unix its whole process group goes with it, so a crashed session's `bun run`
+/// leaves nothing behind. A PID that has since gone to another process is left alone.
+/// Returns the PID that was killed.
+pub fn kill_stale(path: &Path) -> Option<u32> {
+    let record: PidRecord = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())?;
+    remove(path);
+
+    let mut system = System::new();
+    let process = refresh(&mut system, record.pid)?;
+    if let Some(reason) = record.mismatch(process) {
+        tracing::warn!(
+            pid = record.pid,
+            reason,
+            "not killing the PID from a stale PID file; it belongs to another process now"
+        );
+        return None;
+    }
+    kill_tree(process).then_some(record.pid)
+}
+
+/// Kills `process` and, when it leads a process group as `process::isolate` sets up, the
+/// rest of that group too.
+#[cfg(unix)]
+fn kill_tree(process: &Process) -> bool {
+    let pid = process.pid().as_u32() as libc::pid_t;
+    // SAFETY: `getpgid` and `killpg` have no memory-safety preconditions. The group is
+    // only signalled when `pid` leads it, so the app's own group is never hit.
+    unsafe {
+        if libc::getpgid(pid) == pid {
+            return libc::killpg(pid, libc::SIGKILL) == 0;
+        }
+    }
+    process.kill()
+}
+
+#[cfg(not(unix))]
+fn kill_tree(process: &Process) -> bool {
+    process.kill()
+}
+
+/// Runs `kill_stale` on every instance PID file left in `dir`, returning the PIDs killed.
+pub fn kill_all_stale(dir: &Path) -> Vec<u32> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut killed = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_pid_file = path.extension().is_some_and(|ext| ext == PID_FILE_EXT)
+            && path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| stem.starts_with(PID_FILE_PREFIX));
+        if is_pid_file {
+            killed.extend(kill_stale(&path));
+        }
+    }
+    killed
+}
@@ -0,0 +1,46 @@
+use std::process::Command;
+
+/// Lowest scheduling priority a nice value can ask for.
+pub const MAX_NICE: i32 = 19;
+
+/// Windows `BELOW_NORMAL_PRIORITY_CLASS` process creation flag.
+#[cfg(windows)]
+const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+
+/// Whether `nice` lowers the priority rather than raising it, which needs privileges.
+pub fn is_valid_nice(nice: i32) -> bool {
+    (0..=MAX_NICE).contains(&nice)
+}
+
+/// Makes the child run at a lower scheduling priority when `nice` is above zero: that
+/// nice value on Unix, the below-normal priority class on Windows.
+#[cfg(unix)]
+pub fn lower(command: &mut Command, nice: i32) {
+    use std::os::unix::process::CommandExt;
+    if nice == 0 {
+        return;
+    }
+    // SAFETY: the hook only calls `setpriority`, which is async-signal-safe.
+    unsafe {
+        command.pre_exec(move || {
+            if libc::setpriority(libc::PRIO_PROCESS, 0, nice) == 0 {
+                Ok(())
+            } else {
+                Err(std::io::Error::last_os_error())
+            }
+        });
+    }
+}
+
+#[cfg(windows)]
+pub fn lower(command: &mut Command, nice: i32) {
+    use std::os::windows::process::CommandExt;
+    if nice > 0 {
+        // Replaces the flags `process::isolate` set, so carry its flag over.
+        command
+            .creation_flags(BELOW_NORMAL_PRIORITY_CLASS | crate::process::CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn lower(_command: &mut Command, _nice: i32) {}
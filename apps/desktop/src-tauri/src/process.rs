@@ -0,0 +1,297 @@
+use std::io;
+use std::process::{Child, Command, ExitStatus};
+use std::time::{Duration, Instant};
+
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How long `kill_blocking` waits for the OS to report the exit.
+const REAP_TIMEOUT: Duration = Duration::from_secs(2);
+/// Pause between attempts when a force-kill fails.
+const KILL_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// How a stopped child ended.
+#[derive(Clone, Copy, Debug)]
+pub struct Exit {
+    pub status: ExitStatus,
+    /// It was already gone when the stop began, so nothing had to be killed.
+    pub already_exited: bool,
+}
+
+/// Why `shutdown` couldn't confirm the child is gone.
+#[derive(Debug)]
+pub enum StopError {
+    /// Every kill attempt failed.
+    Kill { error: io::Error, attempts: u32 },
+    /// The kill went through but the OS didn't report the exit in time.
+    Timeout,
+    /// Checking on the child failed.
+    Wait(io::Error),
+}
+
+/// Asks the child to exit and lets it drain for up to `stop_timeout`, calling `on_drain`
+/// once it has been asked, then force-kills it if it is still alive, retrying a failed
+/// kill `kill_retries` times. A zero `stop_timeout` skips straight to the kill, as does a
+/// child that can't be asked. Fails with `Timeout` if the exit isn't confirmed within
+/// `kill_timeout` of the kill; otherwise the child is reaped so it doesn't linger as a
+/// zombie.
+pub async fn shutdown(
+    child: &mut Child,
+    stop_timeout: Duration,
+    kill_timeout: Duration,
+    kill_retries: u32,
+    on_drain: impl FnOnce(),
+) -> Result<Exit, StopError> {
+    if let Some(status) = child.try_wait().map_err(StopError::Wait)? {
+        return Ok(Exit {
+            status,
+            already_exited: true,
+        });
+    }
+    if !stop_timeout.is_zero() && request_exit(child) {
+        on_drain();
+        if let Some(status) = wait_timeout(child, stop_timeout)
+            .await
+            .map_err(StopError::Wait)?
+        {
+            return Ok(Exit {
+                status,
+                already_exited: false,
+            });
+        }
+    }
+
+    let already_exited = kill(child, kill_retries).await?;
+    let status = wait_timeout(child, kill_timeout)
+        .await
+        .map_err(StopError::Wait)?
+        .ok_or(StopError::Timeout)?;
+    Ok(Exit {
+        status,
+        already_exited,
+    })
+}
+
+/// Force-kills the child, retrying failures other than a denied permission up to
+/// `retries` times. Returns whether it turned out to have exited already, which counts as
+/// success: the point was for it to be gone.
+pub async fn kill(child: &mut Child, retries: u32) -> Result<bool, StopError> {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        if let Ok(Some(_)) = child.try_wait() {
+            return Ok(true);
+        }
+        match child.kill() {
+            Ok(()) => return Ok(false),
+            Err(e) if is_gone(&e) => return Ok(true),
+            Err(error) if attempts > retries || error.kind() == io::ErrorKind::PermissionDenied => {
+                return Err(StopError::Kill { error, attempts });
+            }
+            Err(error) => {
+                tracing::warn!(pid = child.id(), attempts, %error, "kill failed; retrying");
+                tokio::time::sleep(KILL_RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Whether a kill failed only because there was nothing left to kill.
+fn is_gone(error: &io::Error) -> bool {
+    #[cfg(unix)]
+    if error.raw_os_error() == Some(libc::ESRCH) {
+        return true;
+    }
+    error.kind() == io::ErrorKind::InvalidInput
+}
+
+/// Force-kills and reaps the child from a synchronous context.
+pub fn kill_blocking(child: &mut Child) -> io::Result<ExitStatus> {
+    match child.kill() {
+        Err(e) if !is_gone(&e) => return Err(e),
+        _ => {}
+    }
+    let deadline = Instant::now() + REAP_TIMEOUT;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            return Err(reap_timed_out());
+        }
+        std::thread::sleep(EXIT_POLL_INTERVAL);
+    }
+}
+
+async fn wait_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+    }
+}
+
+fn reap_timed_out() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        "process did not exit after being killed",
+    )
+}
+
+/// Sends SIGTERM to the child's process group, so its own subprocesses get the chance to
+/// exit too. Returns whether a graceful exit was requested.
+#[cfg(unix)]
+fn request_exit(child: &Child) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(child.id()) else {
+        return false;
+    };
+    // SAFETY: `kill` has no memory-safety preconditions; the group is led by our child,
+    // see `isolate`. A child outside its own group is signalled alone.
+    unsafe { libc::kill(-pid, libc::SIGTERM) == 0 || libc::kill(pid, libc::SIGTERM) == 0 }
+}
+
+/// Sends Ctrl-Break to the child's process group, which bun treats as SIGBREAK. This only
+/// reaches children sharing the app's console, so a release build without one falls
+/// through to the kill.
+#[cfg(windows)]
+fn request_exit(child: &Child) -> bool {
+    use windows_sys::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+    // SAFETY: `GenerateConsoleCtrlEvent` has no memory-safety preconditions; the group ID
+    // is the child's PID, see `isolate`.
+    unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, child.id()) != 0 }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn request_exit(_child: &Child) -> bool {
+    false
+}
+
+/// Windows `CREATE_NEW_PROCESS_GROUP` process creation flag.
+#[cfg(windows)]
+pub const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+
+/// Starts the child in its own process group, so `ProcessTree` can signal everything it
+/// spawns without touching the app. Job objects are assigned after spawning on Windows;
+/// the group there only lets Ctrl-Break reach the child and not the app.
+#[cfg(unix)]
+pub fn isolate(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+}
+
+/// Creation flags replace each other, so `priority::lower` has to come after and keep this one.
+#[cfg(windows)]
+pub fn isolate(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn isolate(_command: &mut Command) {}
+
+/// Everything a child has spawned, e.g. the processes behind `bun run`. Dropping the tree
+/// kills whatever is left of it, so it should only be dropped once the child itself is
+/// gone: its process group on Unix, a job object on Windows.
+pub struct ProcessTree {
+    #[cfg(unix)]
+    pgid: libc::pid_t,
+    #[cfg(windows)]
+    _job: job::Job,
+}
+
+impl ProcessTree {
+    /// Tracks the descendants of `child`, which must have been spawned after `isolate`.
+    /// Returns `None` if they can't be tracked, leaving only the child itself killable.
+    #[cfg(unix)]
+    pub fn attach(child: &Child) -> Option<Self> {
+        let pid = libc::pid_t::try_from(child.id()).ok()?;
+        // SAFETY: `getpgid` has no memory-safety preconditions.
+        // Never adopt a group the child shares with the app, or dropping it would kill us.
+        (unsafe { libc::getpgid(pid) } == pid).then_some(ProcessTree { pgid: pid })
+    }
+
+    #[cfg(windows)]
+    pub fn attach(child: &Child) -> Option<Self> {
+        match job::Job::assign(child) {
+            Ok(job) => Some(ProcessTree { _job: job }),
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to put server in a job object");
+                None
+            }
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn attach(_child: &Child) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        // SAFETY: `killpg` has no memory-safety preconditions. The PGID can't have been
+        // reused while any process in the group is alive; if none are, this is a no-op.
+        unsafe {
+            libc::killpg(self.pgid, libc::SIGKILL);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod job {
+    use std::io;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// A job object that terminates every process in it when its handle is closed.
+    pub struct Job(HANDLE);
+
+    // SAFETY: a job handle may be used and closed from any thread.
+    unsafe impl Send for Job {}
+    unsafe impl Sync for Job {}
+
+    impl Job {
+        pub fn assign(child: &Child) -> io::Result<Self> {
+            // SAFETY: null attributes and name are allowed; the handle is owned by `Job`
+            // from here on, so every error path closes it.
+            unsafe {
+                let handle = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+                if handle.is_null() {
+                    return Err(io::Error::last_os_error());
+                }
+                let job = Job(handle);
+                let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+                info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+                let set = SetInformationJobObject(
+                    job.0,
+                    JobObjectExtendedLimitInformation,
+                    std::ptr::addr_of!(info).cast(),
+                    std::mem::size_of_val(&info) as u32,
+                );
+                if set == 0 || AssignProcessToJobObject(job.0, child.as_raw_handle()) == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(job)
+            }
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            // SAFETY: the handle came from `CreateJobObjectW` and is closed only here.
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+}
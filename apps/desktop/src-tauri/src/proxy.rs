@@ -0,0 +1,185 @@
+use crate::error::ApiError;
+use crate::net::{self, Endpoint};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::Method;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Upper bound for a proxied request, including reading the body.
+pub const PROXY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A frontend request to relay to the server.
+pub struct ApiRequest {
+    pub method: String,
+    pub path: String,
+    pub body: Option<String>,
+    pub headers: HashMap<String, String>,
+}
+
+/// An API response relayed back to the frontend.
+#[derive(Serialize)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/// A validated request, ready to send over either transport.
+struct Prepared {
+    method: Method,
+    path: String,
+    headers: HeaderMap,
+    body: Option<String>,
+}
+
+/// Forwards one request to the server at `endpoint`, so the frontend never needs to know
+/// the port or socket, or deal with CORS.
+pub async fn forward(
+    client: &reqwest::Client,
+    endpoint: &Endpoint,
+    request: ApiRequest,
+    token: Option<&str>,
+) -> Result<ApiResponse, ApiError> {
+    let ApiRequest {
+        method,
+        path,
+        body,
+        headers,
+    } = request;
+    if !path.starts_with('/') {
+        return Err(ApiError::InvalidRequest(format!(
+            "path must start with '/', got '{}'",
+            path
+        )));
+    }
+    let method = Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+        .map_err(|_| ApiError::InvalidRequest(format!("unknown method '{}'", method)))?;
+    let mut headers = header_map(headers)?;
+    if let Some(token) = token {
+        let value = HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|_| ApiError::Internal("API token is not a valid header".to_string()))?;
+        headers.insert(AUTHORIZATION, value);
+    }
+    let prepared = Prepared {
+        method,
+        path,
+        headers,
+        body,
+    };
+    match endpoint {
+        Endpoint::Tcp { host, port } => forward_tcp(client, host, *port, prepared).await,
+        Endpoint::Unix(socket) => unix::forward(socket, prepared).await,
+    }
+}
+
+async fn forward_tcp(
+    client: &reqwest::Client,
+    host: &str,
+    port: u16,
+    prepared: Prepared,
+) -> Result<ApiResponse, ApiError> {
+    let url = format!("http://{}{}", net::authority(host, port), prepared.path);
+    let mut request = client
+        .request(prepared.method, url)
+        .headers(prepared.headers)
+        .timeout(PROXY_TIMEOUT);
+    if let Some(body) = prepared.body {
+        request = request.body(body);
+    }
+    let response = request.send().await.map_err(failed)?;
+
+    let status = response.status().as_u16();
+    let headers = collect_headers(response.headers());
+    let body = response.text().await.map_err(failed)?;
+    Ok(ApiResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::{collect_headers, failed, ApiResponse, Prepared, PROXY_TIMEOUT};
+    use crate::error::ApiError;
+    use http_body_util::{BodyExt, Full};
+    use hyper::body::Bytes;
+    use hyper_util::rt::TokioIo;
+    use reqwest::header::{HeaderValue, HOST};
+    use std::path::Path;
+    use tokio::net::UnixStream;
+
+    /// Sends the request over a fresh HTTP/1.1 connection to the socket at `socket`.
+    pub async fn forward(socket: &Path, prepared: Prepared) -> Result<ApiResponse, ApiError> {
+        let exchange = async {
+            let stream = UnixStream::connect(socket).await.map_err(failed)?;
+            let (mut sender, connection) =
+                hyper::client::conn::http1::handshake(TokioIo::new(stream))
+                    .await
+                    .map_err(failed)?;
+            tauri::async_runtime::spawn(connection);
+
+            let body = Full::new(Bytes::from(prepared.body.unwrap_or_default()));
+            let mut request = hyper::Request::builder()
+                .method(prepared.method)
+                .uri(prepared.path)
+                .body(body)
+                .map_err(|e| ApiError::InvalidRequest(e.to_string()))?;
+            *request.headers_mut() = prepared.headers;
+            request
+                .headers_mut()
+                .insert(HOST, HeaderValue::from_static("localhost"));
+            let response = sender.send_request(request).await.map_err(failed)?;
+
+            let status = response.status().as_u16();
+            let headers = collect_headers(response.headers());
+            let body = response.into_body().collect().await.map_err(failed)?;
+            Ok(ApiResponse {
+                status,
+                headers,
+                body: String::from_utf8_lossy(&body.to_bytes()).into_owned(),
+            })
+        };
+        tokio::time::timeout(PROXY_TIMEOUT, exchange)
+            .await
+            .map_err(|_| ApiError::RequestFailed("request timed out".to_string()))?
+    }
+}
+
+#[cfg(not(unix))]
+mod unix {
+    use super::{ApiResponse, Prepared};
+    use crate::error::ApiError;
+    use std::path::Path;
+
+    pub async fn forward(_socket: &Path, _prepared: Prepared) -> Result<ApiResponse, ApiError> {
+        Err(ApiError::RequestFailed(
+            "Unix sockets are not supported on this platform".to_string(),
+        ))
+    }
+}
+
+fn failed(e: impl std::fmt::Display) -> ApiError {
+    ApiError::RequestFailed(e.to_string())
+}
+
+fn collect_headers(headers: &HeaderMap) -> HashMap<String, String> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+fn header_map(headers: HashMap<String, String>) -> Result<HeaderMap, ApiError> {
+    let mut map = HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers {
+        let header = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| ApiError::InvalidRequest(format!("invalid header name '{}'", name)))?;
+        let value = HeaderValue::from_str(&value).map_err(|_| {
+            ApiError::InvalidRequest(format!("invalid value for header '{}'", name))
+        })?;
+        map.insert(header, value);
+    }
+    Ok(map)
+}
@@ -0,0 +1,78 @@
+use crate::error::ApiError;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+const BUN_BINARY: &str = "bun.exe";
+#[cfg(not(windows))]
+const BUN_BINARY: &str = "bun";
+
+/// Install locations GUI launches often miss because their PATH is minimal.
+fn common_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+        dirs.push(PathBuf::from(home).join(".bun").join("bin"));
+    }
+    dirs.push(PathBuf::from("/usr/local/bin"));
+    dirs.push(PathBuf::from("/opt/homebrew/bin"));
+    dirs
+}
+
+/// Locates the bun binary: the configured path if set, otherwise PATH and then the
+/// common install dirs.
+pub fn find_bun(configured: Option<&Path>) -> Result<PathBuf, ApiError> {
+    if let Some(path) = configured {
+        return if path.is_file() {
+            Ok(path.to_path_buf())
+        } else {
+            Err(ApiError::BunNotFound {
+                searched: vec![path.to_path_buf()],
+            })
+        };
+    }
+
+    let path_dirs = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let searched: Vec<PathBuf> = path_dirs
+        .into_iter()
+        .chain(common_dirs())
+        .map(|dir| dir.join(BUN_BINARY))
+        .collect();
+
+    match searched.iter().find(|candidate| candidate.is_file()) {
+        Some(found) => Ok(found.clone()),
+        None => Err(ApiError::BunNotFound { searched }),
+    }
+}
+
+#[derive(Serialize)]
+pub struct BunInfo {
+    pub path: PathBuf,
+    pub version: String,
+}
+
+/// Runs `bun --version` and returns the parsed version string (e.g. `1.2.3`).
+pub fn bun_version(bun: &Path) -> Result<String, ApiError> {
+    let output = std::process::Command::new(bun)
+        .arg("--version")
+        .output()
+        .map_err(|e| ApiError::BunVersionFailed(e.to_string()))?;
+    if !output.status.success() {
+        return Err(ApiError::BunVersionFailed(format!(
+            "`bun --version` exited with {}",
+            output.status
+        )));
+    }
+
+    let version = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .trim_start_matches('v')
+        .to_string();
+    if version.is_empty() {
+        return Err(ApiError::BunVersionFailed(
+            "`bun --version` printed nothing".to_string(),
+        ));
+    }
+    Ok(version)
+}
@@ -0,0 +1,981 @@
+use crate::config::{Config, LaunchMode, ReadinessStrategy, RestartPolicy};
+use crate::error::ApiError;
+use crate::events::{self, ServerEvent};
+use crate::health::{self, LatencyHistory, ProbeOutcome, ProbeState};
+use crate::logs::{
+    self, Banner, LineBuffer, LogBuffer, LogLine, LogSink, LogStream, StartupCapture,
+    STDERR_TAIL_LINES,
+};
+use crate::metrics::Counters;
+use crate::process::{Exit, ProcessTree, StopError};
+use crate::{net, pidfile, priority, process, runtime, sync, token, unwind, ApiState};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+use tokio::sync::{oneshot, Mutex, Notify};
+
+/// How long a command waits for an in-flight transition to settle before giving up.
+const TRANSITION_WAIT: Duration = Duration::from_secs(10);
+/// With readiness checks off, how long the child must survive before it counts as running.
+const UNCHECKED_SETTLE: Duration = Duration::from_millis(200);
+
+/// Lifecycle of a server: `Stopped -> Starting -> Running -> Stopping -> Stopped`, with
+/// `Crashed` reachable whenever the process dies on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerStatus {
+    Stopped,
+    Starting,
+    Running,
+    Crashed,
+    Stopping,
+}
+
+impl ServerStatus {
+    /// Whether the lifecycle allows moving from `self` to `next`.
+    pub fn can_become(self, next: ServerStatus) -> bool {
+        use ServerStatus::*;
+        matches!(
+            (self, next),
+            (Stopped | Crashed, Starting)
+                | (Starting, Running | Crashed | Stopping | Stopped)
+                | (Running, Stopping | Crashed)
+                | (Crashed, Stopping | Stopped)
+                | (Stopping, Stopped)
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct StartupTiming {
+    pub timeout: Duration,
+    pub poll_interval: Duration,
+    /// Upper bound for a single health probe, so a hung socket can't stall the loop.
+    pub probe_timeout: Duration,
+}
+
+impl StartupTiming {
+    /// Number of health polls that fit in the timeout, rounded up.
+    fn attempts(&self) -> u32 {
+        let interval = self.poll_interval.as_millis().max(1);
+        self.timeout.as_millis().div_ceil(interval).max(1) as u32
+    }
+}
+
+/// Everything that can vary between two launches of the same server.
+pub struct LaunchOptions {
+    pub timing: StartupTiming,
+    pub env: HashMap<String, String>,
+    /// Appended to the spawn command after the fixed arguments.
+    pub extra_args: Vec<String>,
+}
+
+/// Outcome of a start, so the frontend doesn't have to parse `message`.
+#[derive(Clone, Debug, Serialize)]
+pub struct StartResult {
+    pub status: ServerStatus,
+    pub port: u16,
+    /// Set instead of a meaningful `port` when the server listens on a Unix socket.
+    pub socket: Option<PathBuf>,
+    pub pid: Option<u32>,
+    /// The server was already starting or running, so nothing new was spawned.
+    pub already_running: bool,
+    pub message: String,
+}
+
+/// How a start ended, for callers that want to handle a failed startup themselves rather
+/// than get an error. Failures that happen before anything is spawned are still errors.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum StartOutcome {
+    Ready {
+        #[serde(flatten)]
+        result: StartResult,
+        elapsed_ms: u64,
+    },
+    TimedOut {
+        error: ApiError,
+        /// Everything the child wrote during this startup.
+        captured_logs: Vec<LogLine>,
+    },
+    CrashedDuringStartup {
+        exit_code: Option<i32>,
+        error: ApiError,
+        captured_logs: Vec<LogLine>,
+    },
+}
+
+impl StartOutcome {
+    /// The outcome as `start_api_server` reports it.
+    pub fn into_result(self) -> Result<StartResult, ApiError> {
+        match self {
+            StartOutcome::Ready { result, .. } => Ok(result),
+            StartOutcome::TimedOut { error, .. }
+            | StartOutcome::CrashedDuringStartup { error, .. } => Err(error),
+        }
+    }
+}
+
+/// Outcome of a stop.
+#[derive(Clone, Debug, Serialize)]
+pub struct StopResult {
+    pub was_running: bool,
+    /// The child had already exited on its own, e.g. a crash the watcher hadn't seen yet.
+    pub already_exited: bool,
+    pub message: String,
+}
+
+impl StopResult {
+    pub fn from_exit(exit: Option<Exit>) -> Self {
+        match exit {
+            Some(exit) if exit.already_exited => StopResult {
+                was_running: true,
+                already_exited: true,
+                message: format!("API server had already exited ({})", exit.status),
+            },
+            Some(exit) => StopResult {
+                was_running: true,
+                already_exited: false,
+                message: format!("API server stopped ({})", exit.status),
+            },
+            None => StopResult {
+                was_running: false,
+                already_exited: false,
+                message: "No API server running".to_string(),
+            },
+        }
+    }
+}
+
+/// How stopping one instance went, as part of `stop_all`.
+#[derive(Clone, Debug, Serialize)]
+pub struct InstanceStop {
+    pub instance: String,
+    /// Unset when the stop failed.
+    pub result: Option<StopResult>,
+    pub error: Option<ApiError>,
+}
+
+/// Lifetime counters for spotting a flapping server; only a user-initiated start resets them.
+#[derive(Clone, Copy, Default, Serialize)]
+pub struct ServerStats {
+    pub crash_count: u32,
+    pub restart_count: u32,
+    pub last_exit_code: Option<i32>,
+}
+
+/// One managed API server instance and its child process.
+pub struct ServerHandle {
+    pub name: String,
+    pub port: Mutex<u16>,
+    /// Unix socket the current child listens on, instead of `port`.
+    pub socket: Mutex<Option<PathBuf>>,
+    pub child: Mutex<Option<Child>>,
+    /// The child's own subprocesses, killed once the child is gone.
+    tree: Mutex<Option<ProcessTree>>,
+    pub status: Mutex<ServerStatus>,
+    /// Signalled on every status change so commands can wait out transitions.
+    status_changed: Notify,
+    pub pid: Mutex<Option<u32>>,
+    pub started_at: Mutex<Option<Instant>>,
+    pub stderr_tail: LineBuffer,
+    pub logs: LogBuffer,
+    /// Filled by the log sink while `start_detailed` waits on a startup.
+    pub startup_capture: StartupCapture,
+    /// Set while the child is being stopped on purpose, so the watcher doesn't revive it.
+    pub intentional_stop: AtomicBool,
+    /// Consecutive automatic restart attempts since the last user-initiated start.
+    pub restart_count: AtomicU32,
+    pub restart_policy: Mutex<RestartPolicy>,
+    pub stats: Mutex<ServerStats>,
+    pub counters: Counters,
+    /// When recent crashes happened, for the circuit breaker.
+    crash_times: Mutex<VecDeque<Instant>>,
+    /// Set by `cancel_startup`; checked between startup phases.
+    cancel_requested: AtomicBool,
+    /// Wakes the startup loop early when a cancel is requested.
+    cancelled: Notify,
+    /// Most recent failure, kept until cleared so the UI can ask what went wrong.
+    pub last_error: Mutex<Option<ApiError>>,
+    /// Bearer token the current child requires; rotated on every spawn unless
+    /// `persist_token` is set.
+    pub token: Mutex<Option<String>>,
+    pub probes: Mutex<ProbeState>,
+    /// Liveness probe timings from the health monitor, for the current child.
+    pub latencies: Mutex<LatencyHistory>,
+    /// When readiness started failing while liveness still passed.
+    pub not_ready_since: Mutex<Option<Instant>>,
+    /// Where the child's PID is recorded for orphan recovery across app restarts.
+    pid_file: Option<PathBuf>,
+}
+
+impl ServerHandle {
+    pub fn new(
+        name: String,
+        port: u16,
+        restart_policy: RestartPolicy,
+        pid_file: Option<PathBuf>,
+    ) -> Self {
+        ServerHandle {
+            name,
+            port: Mutex::new(port),
+            socket: Mutex::new(None),
+            child: Mutex::new(None),
+            tree: Mutex::new(None),
+            status: Mutex::new(ServerStatus::Stopped),
+            status_changed: Notify::new(),
+            pid: Mutex::new(None),
+            started_at: Mutex::new(None),
+            stderr_tail: Arc::new(std::sync::Mutex::new(VecDeque::with_capacity(
+                STDERR_TAIL_LINES,
+            ))),
+            logs: LogBuffer::default(),
+            startup_capture: StartupCapture::default(),
+            intentional_stop: AtomicBool::new(false),
+            restart_count: AtomicU32::new(0),
+            restart_policy: Mutex::new(restart_policy),
+            stats: Mutex::new(ServerStats::default()),
+            crash_times: Mutex::new(VecDeque::new()),
+            cancel_requested: AtomicBool::new(false),
+            cancelled: Notify::new(),
+            last_error: Mutex::new(None),
+            token: Mutex::new(None),
+            probes: Mutex::new(ProbeState::default()),
+            latencies: Mutex::new(LatencyHistory::default()),
+            counters: Counters::default(),
+            not_ready_since: Mutex::new(None),
+            pid_file,
+        }
+    }
+
+    pub async fn set_status(&self, status: ServerStatus) {
+        *self.status.lock().await = status;
+        self.status_changed.notify_waiters();
+    }
+
+    /// Moves to `next` if the lifecycle allows it, returning the previous status.
+    async fn transition(&self, next: ServerStatus) -> Result<ServerStatus, ApiError> {
+        let mut status = self.status.lock().await;
+        let from = *status;
+        if !from.can_become(next) {
+            return Err(ApiError::InvalidTransition { from, to: next });
+        }
+        *status = next;
+        self.status_changed.notify_waiters();
+        Ok(from)
+    }
+
+    /// Waits until the status is no longer `busy`, returning the status it settled on.
+    async fn wait_while(&self, busy: ServerStatus) -> Result<ServerStatus, ApiError> {
+        let settle = async {
+            loop {
+                let changed = self.status_changed.notified();
+                tokio::pin!(changed);
+                // Register before checking so a change in between isn't missed.
+                changed.as_mut().enable();
+                let status = *self.status.lock().await;
+                if status != busy {
+                    return status;
+                }
+                changed.await;
+            }
+        };
+        tokio::time::timeout(TRANSITION_WAIT, settle)
+            .await
+            .map_err(|_| ApiError::TransitionTimeout(busy))
+    }
+
+    /// Asks an in-progress startup to abort. Returns `false` if nothing is starting.
+    pub async fn cancel_startup(&self) -> bool {
+        let status = self.status.lock().await;
+        if *status != ServerStatus::Starting {
+            return false;
+        }
+        self.cancel_requested.store(true, Ordering::SeqCst);
+        // Keep the watcher from reviving a recovery launch that gets cancelled.
+        self.intentional_stop.store(true, Ordering::SeqCst);
+        self.cancelled.notify_waiters();
+        true
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_requested.load(Ordering::SeqCst)
+    }
+
+    /// Stores new probe results, emitting `health-changed` if the summary flipped.
+    pub async fn set_probes<R: Runtime>(&self, app: &AppHandle<R>, probes: ProbeState) {
+        let previous = std::mem::replace(&mut *self.probes.lock().await, probes);
+        if previous.health() != probes.health() {
+            events::emit_health(app, &self.name, probes.health());
+        }
+    }
+
+    pub async fn reset_probes<R: Runtime>(&self, app: &AppHandle<R>) {
+        self.set_probes(app, ProbeState::default()).await;
+        *self.not_ready_since.lock().await = None;
+    }
+
+    /// How long the current child has been running, if there is one.
+    pub async fn uptime(&self) -> Option<Duration> {
+        let running = self.child.lock().await.is_some();
+        let started_at = *self.started_at.lock().await;
+        started_at.filter(|_| running).map(|at| at.elapsed())
+    }
+
+    /// Records a health monitor probe that took `latency`.
+    pub async fn record_probe(&self, latency: Duration, passed: bool) {
+        self.latencies.lock().await.record(latency, passed);
+        self.counters.probes.fetch_add(1, Ordering::Relaxed);
+        if !passed {
+            self.counters.probe_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub async fn record_crash(&self, exit: ExitStatus) {
+        let mut stats = self.stats.lock().await;
+        stats.crash_count += 1;
+        self.counters.crashes.fetch_add(1, Ordering::Relaxed);
+        stats.last_exit_code = exit.code();
+        self.crash_times.lock().await.push_back(Instant::now());
+    }
+
+    /// Whether at least `crashes` crashes happened within the last `window`.
+    pub async fn circuit_open(&self, crashes: u32, window: Duration) -> bool {
+        let mut times = self.crash_times.lock().await;
+        while times.front().is_some_and(|at| at.elapsed() > window) {
+            times.pop_front();
+        }
+        times.len() >= crashes as usize
+    }
+
+    /// Where the app reaches the current child.
+    pub async fn endpoint(&self, config: &Config) -> net::Endpoint {
+        match self.socket.lock().await.clone() {
+            Some(path) => net::Endpoint::Unix(path),
+            None => net::Endpoint::Tcp {
+                host: config.connect_host().to_string(),
+                port: *self.port.lock().await,
+            },
+        }
+    }
+
+    /// Describes the current child for a start that ended in `status`. Takes the status
+    /// rather than reading it, since callers may be holding its lock.
+    async fn start_result(
+        &self,
+        status: ServerStatus,
+        already_running: bool,
+        message: impl FnOnce(&str) -> String,
+    ) -> StartResult {
+        let message = message(&self.location().await);
+        let port = *self.port.lock().await;
+        let socket = self.socket.lock().await.clone();
+        let pid = *self.pid.lock().await;
+        StartResult {
+            status,
+            port,
+            socket,
+            pid,
+            already_running,
+            message,
+        }
+    }
+
+    /// The port or socket, as shown in status messages.
+    async fn location(&self) -> String {
+        match self.socket.lock().await.as_deref() {
+            Some(path) => format!("socket {}", path.display()),
+            None => format!("port {}", *self.port.lock().await),
+        }
+    }
+
+    /// Closes the circuit breaker, as a manual start should.
+    pub async fn reset_breaker(&self) {
+        self.crash_times.lock().await.clear();
+    }
+
+    pub async fn record_restart(&self) {
+        self.stats.lock().await.restart_count += 1;
+        self.counters.restarts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Remembers `error` for `get_last_error`. Cancellations are deliberate and skipped.
+    pub async fn record_error(&self, error: &ApiError) {
+        if !matches!(error, ApiError::Cancelled) {
+            *self.last_error.lock().await = Some(error.clone());
+        }
+    }
+
+    /// The captured stderr tail, if the child wrote anything, for startup errors.
+    pub fn stderr_tail(&self) -> Option<String> {
+        let tail = sync::lock(&self.stderr_tail, "stderr tail");
+        if tail.is_empty() {
+            return None;
+        }
+        Some(tail.iter().cloned().collect::<Vec<_>>().join("\n"))
+    }
+
+    fn record_pid(&self, pid: u32) {
+        if let Some(path) = &self.pid_file {
+            let _ = pidfile::write(path, &pidfile::PidRecord::now(pid));
+        }
+    }
+
+    fn clear_pid_file(&self) {
+        if let Some(path) = &self.pid_file {
+            pidfile::remove(path);
+        }
+    }
+}
+
+#[tracing::instrument(skip_all, fields(instance = %server.name))]
+pub async fn launch<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &ApiState,
+    server: &ServerHandle,
+    options: LaunchOptions,
+) -> Result<StartResult, ApiError> {
+    let result = unwind::guard(spawn_and_wait(app, state, server, options)).await;
+    if let Err(ApiError::Internal(_)) = &result {
+        // Don't leave the instance stuck in `Starting`; kill whatever was spawned.
+        let _ = stop_child(app, state, server).await;
+        server.set_status(ServerStatus::Stopped).await;
+    }
+    match &result {
+        Ok(_) => tracing::info!("server ready"),
+        Err(ApiError::Cancelled) => tracing::info!("startup cancelled"),
+        Err(e) => {
+            tracing::warn!(error = %e, "startup failed");
+            server.record_error(e).await;
+        }
+    }
+    result
+}
+
+async fn spawn_and_wait<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &ApiState,
+    server: &ServerHandle,
+    options: LaunchOptions,
+) -> Result<StartResult, ApiError> {
+    let timing = options.timing;
+    let config = state.config().await;
+    if reap_exited(server).await?.is_some() {
+        server.set_status(ServerStatus::Crashed).await;
+    }
+
+    // A start that arrives mid-stop is queued behind it rather than rejected.
+    if *server.status.lock().await == ServerStatus::Stopping {
+        server.wait_while(ServerStatus::Stopping).await?;
+    }
+    // Check and claim the Starting state under one lock so concurrent calls can't both spawn.
+    {
+        let mut status = server.status.lock().await;
+        match *status {
+            current @ ServerStatus::Starting => {
+                let result = server.start_result(current, true, |location| {
+                    format!("API server already starting on {}", location)
+                });
+                return Ok(result.await);
+            }
+            current @ ServerStatus::Running => {
+                let result = server.start_result(current, true, |location| {
+                    format!("API server already running on {}", location)
+                });
+                return Ok(result.await);
+            }
+            ServerStatus::Stopping => return Err(ApiError::Stopping),
+            ServerStatus::Stopped | ServerStatus::Crashed => *status = ServerStatus::Starting,
+        }
+        server.cancel_requested.store(false, Ordering::SeqCst);
+        server.status_changed.notify_waiters();
+    }
+    events::emit(app, &server.name, ServerEvent::Starting);
+    if !config.uses_socket() && net::is_wildcard(&config.host) {
+        let message = format!(
+            "API server is binding to {}, which exposes it to every device on the network",
+            config.host
+        );
+        events::emit(app, &server.name, ServerEvent::Warning { message });
+    }
+    server.reset_probes(app).await;
+    server.latencies.lock().await.clear();
+    server.intentional_stop.store(false, Ordering::SeqCst);
+    sync::lock(&server.stderr_tail, "stderr tail").clear();
+
+    let socket = if config.uses_socket() {
+        let path = state.socket_path(&server.name);
+        if let Err(e) = net::prepare_socket(&path) {
+            server.set_status(ServerStatus::Stopped).await;
+            return Err(e);
+        }
+        Some(path)
+    } else {
+        None
+    };
+    *server.socket.lock().await = socket.clone();
+    // Port 0 is resolved here and stored, so `get_api_info` reports the real port.
+    let port = if socket.is_some() {
+        *server.port.lock().await
+    } else {
+        let mut port = server.port.lock().await;
+        let requested = *port;
+        let free = match requested {
+            0 => net::ephemeral_port(&config.host),
+            preferred => net::find_free_port(&config.host, preferred),
+        };
+        match free {
+            Ok(free) => {
+                *port = free;
+                events::emit_port_assigned(app, &server.name, free, requested);
+            }
+            Err(e) => {
+                drop(port);
+                server.set_status(ServerStatus::Stopped).await;
+                return Err(e);
+            }
+        }
+        *port
+    };
+    let api_token = if config.persist_token {
+        state.persistent_token().await
+    } else {
+        token::generate()
+    };
+    *server.token.lock().await = Some(api_token.clone());
+    let built = command(
+        &config,
+        port,
+        socket.as_deref(),
+        &api_token,
+        &options.extra_args,
+    );
+    let mut command = match built {
+        Ok(command) => command,
+        Err(e) => {
+            server.set_status(ServerStatus::Stopped).await;
+            return Err(e);
+        }
+    };
+    if server.is_cancelled() {
+        return Err(abandon_startup(app, server).await);
+    }
+    let sink = LogSink {
+        instance: server.name.clone(),
+        subscribers: state.log_subscribers.clone(),
+        logs: server.logs.clone(),
+        file: state.log_file.clone(),
+        redact: Some(api_token.clone()),
+        filter: state.log_filter.clone(),
+        // Checked by `Config::validate`.
+        level_pattern: config
+            .output_level_pattern
+            .as_deref()
+            .and_then(|pattern| regex::Regex::new(pattern).ok()),
+        capture: server.startup_capture.clone(),
+    };
+    let command_line = token::redact(&describe(&command), Some(&api_token));
+    tracing::info!(port, command = %command_line, "spawning server");
+    sink.write(LogStream::System, format!("$ {}", command_line));
+    command
+        .current_dir(&config.working_dir)
+        .envs(&options.env)
+        .env(token::TOKEN_ENV, &api_token)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+    process::isolate(&mut command);
+    priority::lower(&mut command, config.nice);
+    let spawned = state.spawner.spawn(&mut command);
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            server.set_status(ServerStatus::Stopped).await;
+            return Err(ApiError::SpawnFailed(e.to_string()));
+        }
+    };
+
+    let mut banner_rx = None;
+    if let Some(stdout) = child.stdout.take() {
+        let banner = config.ready_banner.clone().map(|pattern| {
+            let (ready, rx) = oneshot::channel();
+            banner_rx = Some(rx);
+            Banner { pattern, ready }
+        });
+        logs::spawn_reader(stdout, LogStream::Stdout, sink.clone(), None, banner);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        let tail = Some(server.stderr_tail.clone());
+        logs::spawn_reader(stderr, LogStream::Stderr, sink, tail, None);
+    }
+
+    tracing::debug!(pid = child.id(), "spawned");
+    server.record_pid(child.id());
+    server.counters.spawns.fetch_add(1, Ordering::Relaxed);
+    *server.pid.lock().await = Some(child.id());
+    *server.started_at.lock().await = Some(Instant::now());
+    *server.tree.lock().await = ProcessTree::attach(&child);
+    *server.child.lock().await = Some(child);
+
+    // Without a readiness check, only make sure the child didn't die straight away.
+    if config.readiness == ReadinessStrategy::None {
+        tokio::select! {
+            _ = tokio::time::sleep(UNCHECKED_SETTLE) => {}
+            _ = server.cancelled.notified() => {}
+        }
+        if server.is_cancelled() {
+            stop_child(app, state, server).await?;
+            return Err(ApiError::Cancelled);
+        }
+        if let Some(exit) = reap_exited(server).await? {
+            return Err(exited_during_startup(app, server, exit).await);
+        }
+        return Ok(mark_ready(app, server, port).await);
+    }
+
+    // The stdout banner is the fast path; HTTP polling remains the fallback.
+    let started = Instant::now();
+    // When the server first answered with an error status, and the latest such status.
+    let mut rejected: Option<(Instant, u16)> = None;
+    let max_attempts = timing.attempts();
+    for attempt in 1..=max_attempts {
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        tracing::debug!(attempt, max_attempts, elapsed_ms, "readiness attempt");
+        events::emit_progress(app, &server.name, attempt, max_attempts, elapsed_ms);
+        if server.is_cancelled() {
+            stop_child(app, state, server).await?;
+            return Err(ApiError::Cancelled);
+        }
+        let seen = tokio::select! {
+            seen = wait_for_banner(&mut banner_rx, timing.poll_interval) => seen,
+            _ = server.cancelled.notified() => false,
+        };
+        if server.is_cancelled() {
+            stop_child(app, state, server).await?;
+            return Err(ApiError::Cancelled);
+        }
+        if seen {
+            return Ok(mark_ready(app, server, port).await);
+        }
+        if let Some(exit) = reap_exited(server).await? {
+            return Err(exited_during_startup(app, server, exit).await);
+        }
+        match probe_ready(state, &config, server, timing.probe_timeout).await {
+            ProbeOutcome::Passed => return Ok(mark_ready(app, server, port).await),
+            ProbeOutcome::Rejected(status) => {
+                tracing::debug!(status, "server answered but isn't ready");
+                let since = rejected.map_or_else(Instant::now, |(since, _)| since);
+                rejected = Some((since, status));
+            }
+            ProbeOutcome::Unreachable => {}
+        }
+    }
+
+    // The timeout is what the caller needs to hear about; a child that survives the stop
+    // stays tracked, so stopping it again can still reach it.
+    if let Err(e) = stop_child(app, state, server).await {
+        tracing::warn!(error = %e, "failed to stop the server after its startup timed out");
+    }
+    events::emit(app, &server.name, ServerEvent::TimedOut);
+    Err(ApiError::StartupTimeout {
+        timeout_ms: timing.timeout.as_millis() as u64,
+        last_status: rejected.map(|(_, status)| status),
+        rejected_ms: rejected.map_or(0, |(since, _)| since.elapsed().as_millis() as u64),
+        stderr: server.stderr_tail(),
+    })
+}
+
+/// The program and arguments for `config.launch_mode`, checked to exist. The server is
+/// told to listen on `socket` when given, otherwise on `config.host` and `port`.
+fn command(
+    config: &Config,
+    port: u16,
+    socket: Option<&Path>,
+    token: &str,
+    extra_args: &[String],
+) -> Result<Command, ApiError> {
+    if let Some(template) = &config.command {
+        let mut command = Command::new(&template.program);
+        command
+            .args(template.render(port, &config.host, socket, token))
+            .args(extra_args);
+        return Ok(command);
+    }
+    let mut command = match &config.launch_mode {
+        LaunchMode::BunScript => {
+            let bun = runtime::find_bun(config.bun_path.as_deref())?;
+            if !config.script_path.is_file() {
+                return Err(ApiError::ScriptNotFound(config.script_path.clone()));
+            }
+            let mut command = Command::new(bun);
+            command.arg("run").arg(&config.script_path).arg("api");
+            command
+        }
+        LaunchMode::Binary { path, args } => {
+            if !path.is_file() {
+                return Err(ApiError::BinaryNotFound(path.clone()));
+            }
+            let mut command = Command::new(path);
+            command.args(args);
+            command
+        }
+        LaunchMode::External => return Err(ApiError::ExternalMode),
+    };
+    match socket {
+        Some(path) => command.arg("--socket").arg(path),
+        None => command.args(["--port", &port.to_string(), "--host", &config.host]),
+    };
+    command.args(extra_args);
+    Ok(command)
+}
+
+/// The command line as a user would type it, for the log.
+fn describe(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Records a child that died before it became ready.
+async fn exited_during_startup<R: Runtime>(
+    app: &AppHandle<R>,
+    server: &ServerHandle,
+    exit: ExitStatus,
+) -> ApiError {
+    tracing::warn!(%exit, "exited during startup");
+    server.set_status(ServerStatus::Crashed).await;
+    server.record_crash(exit).await;
+    events::emit(
+        app,
+        &server.name,
+        ServerEvent::Crashed { code: exit.code() },
+    );
+    ApiError::ExitedDuringStartup {
+        status: exit.to_string(),
+        code: exit.code(),
+        stderr: server.stderr_tail(),
+    }
+}
+
+/// Resets a startup that was cancelled before anything was spawned.
+async fn abandon_startup<R: Runtime>(app: &AppHandle<R>, server: &ServerHandle) -> ApiError {
+    server.set_status(ServerStatus::Stopped).await;
+    events::emit(app, &server.name, ServerEvent::Stopped);
+    ApiError::Cancelled
+}
+
+/// Runs one readiness poll for the configured strategy. The banner-only strategy never
+/// passes here; readiness then comes solely from `wait_for_banner`. With no readiness
+/// check the poll loop is skipped entirely.
+async fn probe_ready(
+    state: &ApiState,
+    config: &Config,
+    server: &ServerHandle,
+    timeout: Duration,
+) -> ProbeOutcome {
+    match config.readiness {
+        ReadinessStrategy::HttpGet => {
+            state
+                .probe_http(config, server, &config.ready_path, timeout)
+                .await
+        }
+        ReadinessStrategy::TcpConnect => ProbeOutcome::from_reachable(
+            health::probe_connect(&server.endpoint(config).await, timeout).await,
+        ),
+        ReadinessStrategy::StdoutBanner | ReadinessStrategy::None => ProbeOutcome::Unreachable,
+    }
+}
+
+async fn mark_ready<R: Runtime>(
+    app: &AppHandle<R>,
+    server: &ServerHandle,
+    port: u16,
+) -> StartResult {
+    server.set_status(ServerStatus::Running).await;
+    let probes = ProbeState {
+        live: Some(true),
+        ready: Some(true),
+    };
+    server.set_probes(app, probes).await;
+    events::emit(app, &server.name, ServerEvent::Ready { port });
+    let result = server.start_result(ServerStatus::Running, false, |location| {
+        format!("API server started on {}", location)
+    });
+    result.await
+}
+
+/// Sleeps for `interval`, returning `true` early if the readiness banner shows up.
+async fn wait_for_banner(banner: &mut Option<oneshot::Receiver<()>>, interval: Duration) -> bool {
+    let Some(rx) = banner.as_mut() else {
+        tokio::time::sleep(interval).await;
+        return false;
+    };
+    tokio::select! {
+        seen = rx => {
+            // Either way the receiver is spent; a closed pipe just means no banner.
+            *banner = None;
+            seen.is_ok()
+        }
+        _ = tokio::time::sleep(interval) => false,
+    }
+}
+
+/// Clears the managed child if it has already exited, returning its exit status.
+pub async fn reap_exited(server: &ServerHandle) -> Result<Option<ExitStatus>, ApiError> {
+    let mut child_lock = server.child.lock().await;
+    let exit = match child_lock.as_mut() {
+        Some(child) => child
+            .try_wait()
+            .map_err(|e| ApiError::ProcessQuery(e.to_string()))?,
+        None => None,
+    };
+    if exit.is_some() {
+        *child_lock = None;
+        // Subprocesses of a crashed server would otherwise outlive it.
+        server.tree.lock().await.take();
+        server.clear_pid_file();
+    }
+    Ok(exit)
+}
+
+/// Stops the child on the user's behalf, suppressing auto-restart until the next start.
+#[tracing::instrument(skip_all, fields(instance = %server.name))]
+pub async fn terminate<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &ApiState,
+    server: &ServerHandle,
+) -> Result<Option<Exit>, ApiError> {
+    let result = unwind::guard(stop_on_request(app, state, server)).await;
+    if let Err(ApiError::Internal(_)) = &result {
+        // Don't leave the instance stuck in `Stopping`; a surviving child can be retried.
+        let status = if server.child.lock().await.is_some() {
+            ServerStatus::Running
+        } else {
+            ServerStatus::Stopped
+        };
+        server.set_status(status).await;
+    }
+    if let Err(e) = &result {
+        tracing::warn!(error = %e, "stop failed");
+        server.record_error(e).await;
+    }
+    result
+}
+
+async fn stop_on_request<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &ApiState,
+    server: &ServerHandle,
+) -> Result<Option<Exit>, ApiError> {
+    server.intentional_stop.store(true, Ordering::SeqCst);
+    // Copied out so the status lock isn't held across the arms, which take it again.
+    let status = *server.status.lock().await;
+    match status {
+        // Let an in-flight stop finish instead of racing it for the child.
+        ServerStatus::Stopping => {
+            server.wait_while(ServerStatus::Stopping).await?;
+            return Ok(None);
+        }
+        ServerStatus::Starting => {}
+        _ => return stop_child(app, state, server).await,
+    }
+    // Stopping a server that is still starting cancels the startup, which cleans up.
+    server.cancel_startup().await;
+    server.wait_while(ServerStatus::Starting).await?;
+    stop_child(app, state, server).await
+}
+
+/// Gracefully stops the managed child and reaps it. Returns how it exited, or `None` if
+/// no child was running.
+pub async fn stop_child<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &ApiState,
+    server: &ServerHandle,
+) -> Result<Option<Exit>, ApiError> {
+    let mut child_lock = server.child.lock().await;
+    if let Some(mut child) = child_lock.take() {
+        let previous = match server.transition(ServerStatus::Stopping).await {
+            Ok(previous) => previous,
+            Err(e) => {
+                *child_lock = Some(child);
+                return Err(e);
+            }
+        };
+        let config = state.config().await;
+        let draining = || {
+            let timeout_ms = config.stop_timeout_ms;
+            events::emit(app, &server.name, ServerEvent::Draining { timeout_ms });
+        };
+        let exit = match process::shutdown(
+            &mut child,
+            config.stop_timeout(),
+            config.kill_timeout(),
+            config.kill_retries,
+            draining,
+        )
+        .await
+        {
+            Ok(exit) => exit,
+            Err(e) => {
+                // Keep tracking a child we couldn't confirm dead, so a retry can reach it.
+                let pid = child.id();
+                let error = match e {
+                    StopError::Timeout => ApiError::KillTimeout {
+                        pid,
+                        timeout_ms: config.kill_timeout_ms,
+                    },
+                    StopError::Kill { error, .. }
+                        if error.kind() == io::ErrorKind::PermissionDenied =>
+                    {
+                        ApiError::KillDenied { pid }
+                    }
+                    StopError::Kill { error, attempts } => ApiError::KillFailed {
+                        pid,
+                        attempts,
+                        reason: error.to_string(),
+                    },
+                    StopError::Wait(error) => ApiError::StopFailed(error.to_string()),
+                };
+                *child_lock = Some(child);
+                server.set_status(previous).await;
+                return Err(error);
+            }
+        };
+        let status = exit.status;
+        tracing::info!(
+            instance = %server.name,
+            %status,
+            already_exited = exit.already_exited,
+            "server stopped"
+        );
+        server.tree.lock().await.take();
+        server.clear_pid_file();
+        if let Some(path) = server.socket.lock().await.take() {
+            let _ = std::fs::remove_file(path);
+        }
+        server.reset_probes(app).await;
+        *server.token.lock().await = None;
+        server.set_status(ServerStatus::Stopped).await;
+        events::emit(app, &server.name, ServerEvent::Stopped);
+        Ok(Some(exit))
+    } else {
+        server.set_status(ServerStatus::Stopped).await;
+        Ok(None)
+    }
+}
+
+/// Force-kills the child from a synchronous context, e.g. while the app is closing.
+pub fn kill_blocking(server: &ServerHandle) {
+    if let Some(mut child) = server.child.blocking_lock().take() {
+        let _ = process::kill_blocking(&mut child);
+        server.tree.blocking_lock().take();
+        server.clear_pid_file();
+    }
+}
@@ -0,0 +1,153 @@
+use crate::config::{FileConfig, RestartPolicy};
+use crate::ApiState;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Listener, Manager, Runtime};
+use tauri_plugin_store::{Store, StoreExt};
+
+/// Settings file shared with the frontend through `tauri-plugin-store`.
+pub const STORE_FILE: &str = "settings.json";
+const STORE_CHANGE_EVENT: &str = "store://change";
+
+/// The non-sensitive settings the UI edits directly. Precedence for each is: environment
+/// variable, then this store, then `yep.toml`, then the built-in default.
+#[derive(Default)]
+pub struct StoreSettings {
+    pub port: Option<u16>,
+    pub autostart: Option<bool>,
+    pub notifications: Option<bool>,
+    pub restart_policy: Option<RestartPolicy>,
+    pub toggle_shortcut: Option<String>,
+}
+
+/// The keys `StoreSettings` reads, each named after its `yep.toml` field.
+const KEYS: [&str; 5] = [
+    "port",
+    "autostart",
+    "notifications",
+    "restart_policy",
+    "toggle_shortcut",
+];
+
+impl StoreSettings {
+    pub fn load<R: Runtime>(store: &Store<R>) -> Self {
+        StoreSettings {
+            port: get(store, "port"),
+            autostart: get(store, "autostart"),
+            notifications: get(store, "notifications"),
+            restart_policy: get(store, "restart_policy"),
+            toggle_shortcut: get(store, "toggle_shortcut"),
+        }
+    }
+
+    /// Lays the stored values over `file`, so env overrides still win in `Config::resolve`.
+    /// Returns the fields `yep.toml` sets to something else, which the store now hides.
+    pub fn overlay(self, file: &mut FileConfig) -> Vec<&'static str> {
+        let mut shadowed = Vec::new();
+        layer(&mut shadowed, "port", self.port, &mut file.port);
+        layer(
+            &mut shadowed,
+            "autostart",
+            self.autostart,
+            &mut file.autostart,
+        );
+        layer(
+            &mut shadowed,
+            "notifications",
+            self.notifications,
+            &mut file.notifications,
+        );
+        layer(
+            &mut shadowed,
+            "restart_policy",
+            self.restart_policy,
+            &mut file.restart_policy,
+        );
+        layer(
+            &mut shadowed,
+            "toggle_shortcut",
+            self.toggle_shortcut,
+            &mut file.toggle_shortcut,
+        );
+        shadowed
+    }
+}
+
+fn layer<T: PartialEq>(
+    shadowed: &mut Vec<&'static str>,
+    key: &'static str,
+    stored: Option<T>,
+    file: &mut Option<T>,
+) {
+    let Some(stored) = stored else {
+        return;
+    };
+    if file.as_ref().is_some_and(|value| *value != stored) {
+        shadowed.push(key);
+    }
+    *file = Some(stored);
+}
+
+fn get<R: Runtime, T: DeserializeOwned>(store: &Store<R>, key: &str) -> Option<T> {
+    store
+        .get(key)
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+pub fn open<R: Runtime>(app: &AppHandle<R>) -> Option<Arc<Store<R>>> {
+    app.store(STORE_FILE)
+        .inspect_err(|e| tracing::warn!(error = %e, "settings store unavailable"))
+        .ok()
+}
+
+/// Drops `fields` from the store once `set_config` has written them to `yep.toml`; left
+/// in place they would hide later hand edits to the file.
+pub fn forget<R: Runtime>(app: &AppHandle<R>, fields: &[String]) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    for key in KEYS
+        .iter()
+        .filter(|key| fields.iter().any(|field| field == *key))
+    {
+        store.delete(key);
+    }
+    store.save().map_err(|e| e.to_string())
+}
+
+#[derive(Deserialize)]
+struct StoreChange {
+    path: PathBuf,
+    key: String,
+}
+
+/// Applies edits the frontend makes to the store straight away, without a restart.
+pub fn watch(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen_any(STORE_CHANGE_EVENT, move |event| {
+        let Ok(change) = serde_json::from_str::<StoreChange>(event.payload()) else {
+            return;
+        };
+        if change
+            .path
+            .file_name()
+            .is_none_or(|name| name != STORE_FILE)
+        {
+            return;
+        }
+        let app = handle.clone();
+        tauri::async_runtime::spawn(async move { apply(&app, change).await });
+    });
+}
+
+/// Re-resolves the config rather than patching in the new value, so environment overrides
+/// keep winning and a deleted key falls back to `yep.toml` or the default.
+async fn apply(app: &AppHandle, change: StoreChange) {
+    if !KEYS.contains(&change.key.as_str()) {
+        return;
+    }
+    let state = app.state::<ApiState>();
+    if let Err(e) = crate::reload(app, &state).await {
+        tracing::warn!(key = %change.key, error = %e, "ignoring store change");
+    }
+}
@@ -0,0 +1,46 @@
+use crate::server::ServerStatus;
+use crate::{tray, ApiState, DEFAULT_INSTANCE};
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_global_shortcut::{Builder, GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// The toggle is the only shortcut registered, so every press toggles the default server.
+pub fn plugin() -> TauriPlugin<Wry> {
+    Builder::new()
+        .with_handler(|app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                tauri::async_runtime::spawn(toggle(app.clone()));
+            }
+        })
+        .build()
+}
+
+/// Replaces the registered toggle shortcut. An empty string leaves none registered.
+pub fn register(app: &AppHandle, shortcut: &str) {
+    let shortcuts = app.global_shortcut();
+    let _ = shortcuts.unregister_all();
+    if shortcut.is_empty() {
+        return;
+    }
+    if let Err(e) = shortcuts.register(shortcut) {
+        tracing::warn!(shortcut, error = %e, "failed to register toggle shortcut");
+    }
+}
+
+pub fn is_valid(shortcut: &str) -> bool {
+    shortcut.is_empty() || shortcut.parse::<Shortcut>().is_ok()
+}
+
+/// Starts a stopped server and stops a running one; presses mid-transition are ignored.
+async fn toggle(app: AppHandle) {
+    let state = app.state::<ApiState>();
+    let Ok(server) = state.instance(DEFAULT_INSTANCE).await else {
+        return;
+    };
+    let action = match *server.status.lock().await {
+        ServerStatus::Stopped | ServerStatus::Crashed => "start",
+        ServerStatus::Running => "stop",
+        ServerStatus::Starting | ServerStatus::Stopping => return,
+    };
+    tray::run_action(app.clone(), action.to_string()).await;
+}
@@ -0,0 +1,18 @@
+use std::io;
+use std::process::{Child, Command};
+
+/// Starts server processes. The lifecycle code only ever spawns through this, so tests
+/// can stand in a fake server and run without bun.
+pub trait ProcessSpawner: Send + Sync {
+    /// Spawns `command`, which already has its arguments, environment and pipes set.
+    fn spawn(&self, command: &mut Command) -> io::Result<Child>;
+}
+
+/// Spawns the command as given.
+pub struct OsSpawner;
+
+impl ProcessSpawner for OsSpawner {
+    fn spawn(&self, command: &mut Command) -> io::Result<Child> {
+        command.spawn()
+    }
+}
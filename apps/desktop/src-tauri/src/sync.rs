@@ -0,0 +1,16 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Locks `mutex`, recovering the value if a previous holder panicked; `what` names the
+/// state in the warning that records the incident.
+///
+/// This is safe for every std mutex in the app: the stderr tail, the log buffer, the
+/// log subscribers and the log file writer are only touched by single push, pop, clear
+/// or copy operations, so a panic can't leave them half updated. Lifecycle state
+/// (`status`, `child`, ...) lives behind tokio mutexes, which don't poison.
+pub fn lock<'a, T>(mutex: &'a Mutex<T>, what: &str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned: PoisonError<_>| {
+        tracing::warn!(state = what, "recovered a poisoned lock");
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
+}
@@ -0,0 +1,33 @@
+use super::env_lock;
+use crate::config::{self, Config, FileConfig, DEFAULT_HOST, DEFAULT_PORT};
+use std::path::PathBuf;
+
+fn config_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("yep-{}-{}.toml", name, std::process::id()))
+}
+
+#[test]
+fn saving_keeps_env_overrides_out_of_yep_toml() {
+    let _env = env_lock();
+    let path = config_path("save-env");
+    std::fs::write(&path, "# hand-written\nnotifications = true\n").unwrap();
+    std::env::set_var("YEP_API_PORT", "4567");
+    let current = Config::resolve(&FileConfig::load(&path).unwrap(), None);
+    assert_eq!(current.port, 4567);
+
+    let mut new = current.clone();
+    new.notifications = false;
+    let changed = current.changed_fields(&new);
+    assert_eq!(changed, ["notifications"]);
+    config::save(&path, &new, &changed).unwrap();
+    std::env::remove_var("YEP_API_PORT");
+
+    let file = FileConfig::load(&path).unwrap();
+    assert_eq!(file.port, None);
+    assert_eq!(file.working_dir, None);
+    let reloaded = Config::resolve(&file, None);
+    assert_eq!(reloaded.port, DEFAULT_PORT);
+    assert_eq!(reloaded.host, DEFAULT_HOST);
+    assert!(!reloaded.notifications);
+    let _ = std::fs::remove_file(&path);
+}
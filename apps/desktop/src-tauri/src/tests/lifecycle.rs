@@ -0,0 +1,751 @@
+use super::mock::{FakeServer, MockSpawner};
+use super::{is_alive, Harness};
+use crate::config::{Config, RestartPolicy};
+use crate::error::ApiError;
+use crate::server::ServerStatus;
+use std::time::Duration;
+
+#[test]
+fn start_becomes_ready() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        harness.start().await.unwrap();
+
+        let server = harness.server().await;
+        assert_eq!(harness.status().await, ServerStatus::Running);
+        assert!(harness.has_child().await);
+        assert_eq!(
+            *server.pid.lock().await,
+            harness.spawner.pids().first().copied()
+        );
+        assert_ne!(
+            *server.port.lock().await,
+            0,
+            "port 0 resolves to a real port"
+        );
+    });
+}
+
+#[test]
+fn start_reports_the_assigned_port() {
+    use crate::events::PORT_ASSIGNED_EVENT;
+    use std::sync::{Arc, Mutex};
+    use tauri::Listener;
+
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        let assigned = Arc::new(Mutex::new(None));
+        let sink = assigned.clone();
+        harness
+            .handle()
+            .listen_any(PORT_ASSIGNED_EVENT, move |event| {
+                let payload: serde_json::Value = serde_json::from_str(event.payload()).unwrap();
+                *sink.lock().unwrap() = Some(payload);
+            });
+        let result = harness.start().await.unwrap();
+
+        let port = *harness.server().await.port.lock().await;
+        let payload = assigned
+            .lock()
+            .unwrap()
+            .take()
+            .expect("port-assigned emitted");
+        assert_eq!(payload["port"], port);
+        assert_eq!(payload["requested"], 0);
+        assert_eq!(result.port, port);
+        assert!(!result.already_running);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn stop_drains_before_killing() {
+    use crate::events::SERVER_EVENT;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tauri::Listener;
+
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        let drained = Arc::new(AtomicBool::new(false));
+        let seen = drained.clone();
+        harness.handle().listen_any(SERVER_EVENT, move |event| {
+            if event.payload().contains(r#""type":"draining""#) {
+                seen.store(true, Ordering::SeqCst);
+            }
+        });
+        harness.start().await.unwrap();
+        harness.stop().await.unwrap();
+
+        assert!(drained.load(Ordering::SeqCst));
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+    });
+}
+
+#[test]
+fn start_without_readiness_check_skips_probing() {
+    use crate::config::ReadinessStrategy;
+
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::never_ready()), |config| {
+            config.readiness = ReadinessStrategy::None;
+        });
+        let result = harness.start().await.unwrap();
+
+        assert_eq!(result.status, ServerStatus::Running);
+        assert!(harness.has_child().await);
+    });
+}
+
+#[test]
+fn concurrent_starts_spawn_one_child() {
+    tauri::async_runtime::block_on(async {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |config| {
+            config.port = port;
+        });
+        let (first, second) = tokio::join!(harness.start(), harness.start());
+        let (first, second) = (first.unwrap(), second.unwrap());
+
+        assert_eq!(harness.spawner.pids().len(), 1);
+        assert_eq!(first.already_running, !second.already_running);
+        assert_eq!((first.port, first.socket), (port, None));
+        assert_eq!((second.port, second.socket), (port, None));
+        assert!(
+            harness
+                .wait_for(ServerStatus::Running, Duration::from_secs(5))
+                .await
+        );
+    });
+}
+
+#[test]
+fn output_past_the_pipe_buffer_does_not_stall_startup() {
+    tauri::async_runtime::block_on(async {
+        // About 100KB on each pipe before the server listens.
+        let lines = 1000;
+        let harness = Harness::new(MockSpawner::new(FakeServer::flooding(lines)), |_| {});
+        let result = harness.start().await.unwrap();
+        assert_eq!(result.status, ServerStatus::Running);
+
+        let server = harness.server().await;
+        let last_out = format!("flood out {} ", lines - 1);
+        let last_err = format!("flood err {} ", lines - 1);
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        loop {
+            let logs = server.logs.recent(None);
+            let has = |prefix: &str| logs.iter().any(|line| line.line.starts_with(prefix));
+            if has(&last_out) && has(&last_err) {
+                break;
+            }
+            assert!(
+                std::time::Instant::now() < deadline,
+                "the last lines never reached the buffer"
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    });
+}
+
+#[test]
+fn start_without_readiness_check_still_catches_an_instant_exit() {
+    use crate::config::ReadinessStrategy;
+
+    tauri::async_runtime::block_on(async {
+        let spawner = MockSpawner::new(FakeServer::crashes_after(Duration::ZERO, 3));
+        let harness = Harness::new(spawner, |config| {
+            config.readiness = ReadinessStrategy::None;
+        });
+        let result = harness.start().await;
+
+        assert!(matches!(result, Err(ApiError::ExitedDuringStartup { .. })));
+        assert_eq!(harness.status().await, ServerStatus::Crashed);
+    });
+}
+
+#[test]
+fn detailed_start_reports_a_crash_with_its_exit_code_and_output() {
+    use crate::server::StartOutcome;
+
+    tauri::async_runtime::block_on(async {
+        let spawner = MockSpawner::new(FakeServer::crashes_after(Duration::ZERO, 3));
+        let harness = Harness::new(spawner, |_| {});
+        let state = harness.state();
+        let server = harness.server().await;
+        let options = state.launch_options().await;
+        let outcome = crate::start_detailed(harness.handle(), &state, &server, options)
+            .await
+            .unwrap();
+
+        let StartOutcome::CrashedDuringStartup {
+            exit_code,
+            captured_logs,
+            ..
+        } = outcome
+        else {
+            panic!("expected a crash, got {:?}", outcome);
+        };
+        assert_eq!(exit_code, Some(3));
+        assert!(captured_logs[0].line.starts_with("$ "));
+    });
+}
+
+#[test]
+fn detailed_start_captures_output_the_log_filter_drops() {
+    use crate::logs::{LogFilter, LogStream};
+    use crate::server::StartOutcome;
+
+    tauri::async_runtime::block_on(async {
+        // Prints its listening line on stdout, then dies before it ever turns healthy.
+        let spawner = MockSpawner::new(FakeServer {
+            exit_after: Some((Duration::from_millis(500), 3)),
+            ..FakeServer::responding(503)
+        });
+        let harness = Harness::new(spawner, |_| {});
+        let state = harness.state();
+        *state.log_filter.lock().unwrap() = LogFilter {
+            streams: Some(vec![LogStream::Stderr]),
+            min_level: None,
+        };
+        let server = harness.server().await;
+        let options = state.launch_options().await;
+        let outcome = crate::start_detailed(harness.handle(), &state, &server, options)
+            .await
+            .unwrap();
+
+        let StartOutcome::CrashedDuringStartup { captured_logs, .. } = outcome else {
+            panic!("expected a crash, got {:?}", outcome);
+        };
+        assert!(captured_logs
+            .iter()
+            .any(|line| line.stream == LogStream::Stdout
+                && line.line.contains("fake server listening")));
+        assert!(server
+            .logs
+            .recent(None)
+            .iter()
+            .all(|line| line.stream != LogStream::Stdout));
+        assert!(server.startup_capture.lock().unwrap().is_none());
+    });
+}
+
+#[test]
+fn metrics_count_spawns_across_restarts() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        harness.start().await.unwrap();
+        harness.stop().await.unwrap();
+        harness.start().await.unwrap();
+
+        let text = crate::metrics::render(&harness.state()).await;
+        assert!(text.contains("yep_spawns_total{instance=\"default\"} 2\n"));
+        assert!(text.contains("yep_up{instance=\"default\"} 1\n"));
+        assert!(text.contains("# TYPE yep_crashes_total counter"));
+    });
+}
+
+#[test]
+fn startup_polls_the_configured_health_path() {
+    tauri::async_runtime::block_on(async {
+        let path = "/custom/health";
+        let harness = Harness::new(MockSpawner::new(FakeServer::serving_at(path)), |config| {
+            config.health_path = path.to_string();
+            config.ready_path = path.to_string();
+        });
+        let result = harness.start().await.unwrap();
+        assert_eq!(result.status, ServerStatus::Running);
+
+        // The default path only ever gets a 404 from this server.
+        let harness = Harness::new(MockSpawner::new(FakeServer::serving_at(path)), |config| {
+            config.startup_timeout_ms = 500;
+        });
+        let result = harness.start().await;
+        assert!(
+            matches!(
+                result,
+                Err(ApiError::StartupTimeout {
+                    last_status: Some(404),
+                    ..
+                })
+            ),
+            "{:?}",
+            result
+        );
+    });
+}
+
+#[test]
+fn slow_server_needs_a_long_enough_startup_timeout() {
+    tauri::async_runtime::block_on(async {
+        let slow = FakeServer {
+            listen_after: Some(Duration::from_secs(3)),
+            ..FakeServer::healthy()
+        };
+        let harness = Harness::new(MockSpawner::new(slow), |config| {
+            config.startup_timeout_ms = 1000;
+        });
+        let result = harness.start().await;
+        assert!(
+            matches!(
+                result,
+                Err(ApiError::StartupTimeout {
+                    timeout_ms: 1000,
+                    ..
+                })
+            ),
+            "{:?}",
+            result
+        );
+
+        let harness = Harness::new(MockSpawner::new(slow), |config| {
+            config.startup_timeout_ms = 10_000;
+        });
+        let result = harness.start().await.unwrap();
+        assert_eq!(result.status, ServerStatus::Running);
+    });
+}
+
+#[test]
+fn configured_env_reaches_the_child() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |config| {
+            config
+                .env
+                .insert("YEP_TEST_INJECTED".to_string(), "from-config".to_string());
+        });
+        harness.start().await.unwrap();
+
+        let env = &harness.spawner.envs()[0];
+        assert_eq!(
+            env.get("YEP_TEST_INJECTED").map(String::as_str),
+            Some("from-config")
+        );
+        assert!(env.contains_key(crate::token::TOKEN_ENV));
+    });
+}
+
+#[test]
+fn startup_timeout_kills_the_child() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::never_ready()), |config| {
+            config.startup_timeout_ms = 500;
+        });
+        let result = harness.start().await;
+
+        assert!(
+            matches!(result, Err(ApiError::StartupTimeout { .. })),
+            "{:?}",
+            result
+        );
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+        assert!(!harness.has_child().await);
+        let pid = harness.spawner.pids()[0];
+        assert!(!is_alive(pid), "timed-out child {} was left running", pid);
+    });
+}
+
+#[test]
+fn startup_timeout_reports_an_error_status() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::responding(503)), |config| {
+            config.startup_timeout_ms = 500;
+        });
+        let result = harness.start().await;
+
+        let Err(error @ ApiError::StartupTimeout { last_status, .. }) = result else {
+            panic!("{:?}", result);
+        };
+        assert_eq!(last_status, Some(503));
+        assert!(error.to_string().contains("responded 503"), "{}", error);
+    });
+}
+
+#[test]
+fn watcher_detects_a_crash() {
+    tauri::async_runtime::block_on(async {
+        let fake = FakeServer::crashes_after(Duration::from_millis(300), 3);
+        let harness = Harness::new(MockSpawner::new(fake), |_| {});
+        harness.start().await.unwrap();
+
+        assert!(harness.sweep_until_crashed(Duration::from_secs(5)).await);
+        let server = harness.server().await;
+        assert_eq!(harness.status().await, ServerStatus::Crashed);
+        assert!(!harness.has_child().await);
+        assert!(matches!(
+            *server.last_error.lock().await,
+            Some(ApiError::Crashed { .. })
+        ));
+        let stats = *server.stats.lock().await;
+        assert_eq!(stats.crash_count, 1);
+        assert_eq!(stats.last_exit_code, Some(3));
+    });
+}
+
+#[test]
+fn crashed_server_is_restarted() {
+    tauri::async_runtime::block_on(async {
+        let spawner = MockSpawner::new(FakeServer::healthy())
+            .then(FakeServer::crashes_after(Duration::from_millis(300), 1));
+        let harness = Harness::new(spawner, |config| {
+            config.restart_policy = RestartPolicy::Always;
+        });
+        harness.start().await.unwrap();
+
+        assert!(harness.sweep_until_crashed(Duration::from_secs(5)).await);
+        assert!(
+            harness
+                .wait_for(ServerStatus::Running, Duration::from_secs(10))
+                .await
+        );
+        let pids = harness.spawner.pids();
+        assert_eq!(pids.len(), 2);
+        assert!(harness.has_child().await);
+        assert_eq!(*harness.server().await.pid.lock().await, Some(pids[1]));
+    });
+}
+
+#[test]
+fn stop_after_an_unnoticed_crash_reports_it_already_exited() {
+    tauri::async_runtime::block_on(async {
+        let spawner = MockSpawner::new(FakeServer::crashes_after(Duration::from_millis(100), 1));
+        let harness = Harness::new(spawner, |_| {});
+        harness.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let exit = harness.stop().await.unwrap().expect("child was tracked");
+        assert!(exit.already_exited);
+        assert_eq!(exit.status.code(), Some(1));
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+    });
+}
+
+#[test]
+fn crashed_server_stays_down_while_shutting_down() {
+    use std::sync::atomic::Ordering;
+
+    tauri::async_runtime::block_on(async {
+        let spawner = MockSpawner::new(FakeServer::crashes_after(Duration::from_millis(300), 1));
+        let harness = Harness::new(spawner, |config| {
+            config.restart_policy = RestartPolicy::Always;
+        });
+        harness.start().await.unwrap();
+        assert!(harness.sweep_until_crashed(Duration::from_secs(5)).await);
+        harness.state().shutting_down.store(true, Ordering::SeqCst);
+
+        // Past the first restart's backoff of at most 1.2s.
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(harness.spawner.pids().len(), 1);
+        assert_eq!(harness.status().await, ServerStatus::Crashed);
+    });
+}
+
+/// The PID of the fake server's own subprocess, once its output line has been read.
+#[cfg(unix)]
+async fn reported_subprocess(harness: &Harness) -> u32 {
+    use super::mock::SUBPROCESS_LINE;
+    use std::time::Instant;
+
+    // The reader thread may not have delivered the line by the time the probe passes.
+    let server = harness.server().await;
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let reported = server.logs.recent(None).iter().find_map(|line| {
+            // Libtest prints the test name on the same line first.
+            let (_, pid) = line.line.split_once(SUBPROCESS_LINE)?;
+            pid.trim().parse::<u32>().ok()
+        });
+        match reported {
+            Some(pid) => return pid,
+            None if Instant::now() < deadline => {
+                tokio::time::sleep(Duration::from_millis(20)).await
+            }
+            None => panic!("fake server never reported its subprocess"),
+        }
+    }
+}
+
+/// Polls until `pid` is gone, giving up after a few seconds.
+#[cfg(unix)]
+async fn wait_until_dead(pid: u32) -> bool {
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while is_alive(pid) && std::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    !is_alive(pid)
+}
+
+#[cfg(unix)]
+#[test]
+fn stop_kills_the_servers_subprocesses() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::with_subprocess()), |_| {});
+        harness.start().await.unwrap();
+        let subprocess = reported_subprocess(&harness).await;
+        assert!(is_alive(subprocess));
+
+        harness.stop().await.unwrap();
+        assert!(
+            wait_until_dead(subprocess).await,
+            "subprocess outlived the server"
+        );
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn startup_timeout_leaves_no_orphaned_subprocess() {
+    tauri::async_runtime::block_on(async {
+        let never_ready = FakeServer {
+            subprocess: true,
+            ..FakeServer::never_ready()
+        };
+        let harness = Harness::new(MockSpawner::new(never_ready), |config| {
+            config.startup_timeout_ms = 1000;
+        });
+        let result = harness.start().await;
+        let subprocess = reported_subprocess(&harness).await;
+
+        assert!(matches!(result, Err(ApiError::StartupTimeout { .. })));
+        assert!(!is_alive(harness.spawner.pids()[0]));
+        assert!(
+            wait_until_dead(subprocess).await,
+            "subprocess outlived the timed-out server"
+        );
+    });
+}
+
+#[test]
+fn diagnostics_strip_tokens_and_env_values() {
+    tauri::async_runtime::block_on(async {
+        let secret = "diagnostics-test-secret";
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |config| {
+            config.env.insert("SECRET".to_string(), secret.to_string());
+            config.extra_args = vec![format!("--password={}", secret)];
+        });
+        harness.start().await.unwrap();
+        let server = harness.server().await;
+        let token = server.token.lock().await.clone().unwrap();
+        server.logs.push(crate::logs::LogLine {
+            instance: server.name.clone(),
+            stream: crate::logs::LogStream::Stdout,
+            line: format!("token {} secret {}", token, secret),
+            ts: 0,
+            seq: 0,
+        });
+
+        let bundle = crate::diagnostics::collect(harness.handle(), &harness.state()).await;
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert!(!json.contains(secret));
+        assert!(!json.contains(&token));
+        assert_eq!(bundle.instances[0].status, ServerStatus::Running);
+        assert!(bundle.instances[0]
+            .logs
+            .last()
+            .unwrap()
+            .line
+            .starts_with("token "));
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn stop_force_kills_once_the_stop_timeout_passes() {
+    use std::os::unix::process::ExitStatusExt;
+    use std::time::Instant;
+
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::ignoring_term()), |config| {
+            config.stop_timeout_ms = 300;
+        });
+        harness.start().await.unwrap();
+        let stopping = Instant::now();
+        let status = harness
+            .stop()
+            .await
+            .unwrap()
+            .expect("child was running")
+            .status;
+
+        assert!(stopping.elapsed() >= Duration::from_millis(300));
+        assert_eq!(status.signal(), Some(libc::SIGKILL));
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn stop_lets_the_server_exit_on_sigterm() {
+    use std::os::unix::process::ExitStatusExt;
+
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        harness.start().await.unwrap();
+        let status = harness
+            .stop()
+            .await
+            .unwrap()
+            .expect("child was running")
+            .status;
+
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+    });
+}
+
+#[cfg(windows)]
+#[test]
+fn stop_without_a_timeout_terminates_right_away() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |config| {
+            config.stop_timeout_ms = 0;
+        });
+        harness.start().await.unwrap();
+        let pid = harness.spawner.pids()[0];
+        harness.stop().await.unwrap().expect("child was running");
+
+        assert!(!is_alive(pid));
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+    });
+}
+
+#[test]
+fn monitor_records_probe_latency() {
+    use std::sync::atomic::Ordering;
+    use std::time::Instant;
+
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |config| {
+            config.health_monitor = true;
+            config.health_check_interval_ms = 1;
+        });
+        harness.state().health_monitor.store(true, Ordering::SeqCst);
+        harness.start().await.unwrap();
+        let mut last_probe = Instant::now();
+        for _ in 0..3 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            crate::watcher::sweep(harness.handle(), &mut last_probe).await;
+        }
+
+        let summary = harness.server().await.latencies.lock().await.summary();
+        assert_eq!(summary.samples, 3);
+        assert_eq!(summary.failures, 0);
+        assert!(summary.min_ms <= summary.p95_ms && summary.p95_ms <= summary.max_ms);
+    });
+}
+
+#[test]
+fn custom_command_gets_the_port_substituted() {
+    use crate::config::CommandTemplate;
+
+    tauri::async_runtime::block_on(async {
+        let mut template = CommandTemplate {
+            program: std::env::current_exe()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned(),
+            args: vec!["--host".to_string(), "{host}".to_string()],
+        };
+        let mut config = super::test_config();
+        config.command = Some(template.clone());
+        assert!(matches!(
+            config.validate(),
+            Err(ApiError::InvalidConfig {
+                field: "command",
+                ..
+            })
+        ));
+
+        template
+            .args
+            .extend(["--port".to_string(), "{port}".to_string()]);
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |config| {
+            config.command = Some(template);
+        });
+        let result = harness.start().await.unwrap();
+        assert_eq!(result.status, ServerStatus::Running);
+        let spawned = harness.server().await.logs.recent(None)[0].line.clone();
+        assert!(
+            spawned.contains(&format!("--port {}", result.port)),
+            "{}",
+            spawned
+        );
+    });
+}
+
+#[test]
+fn stop_all_stops_every_instance() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        let state = harness.state();
+        harness.start().await.unwrap();
+        let worker = state.instance("worker").await.unwrap();
+        let options = state.launch_options().await;
+        crate::start(harness.handle(), &state, &worker, options)
+            .await
+            .unwrap();
+        // Never started, so there's nothing to stop.
+        state.instance("idle").await.unwrap();
+
+        let stops = crate::stop_instances(harness.handle(), &state).await;
+
+        let summary: Vec<(&str, bool)> = stops
+            .iter()
+            .map(|stop| {
+                (
+                    stop.instance.as_str(),
+                    stop.result.as_ref().unwrap().was_running,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [("default", true), ("idle", false), ("worker", true)]
+        );
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+        assert_eq!(*worker.status.lock().await, ServerStatus::Stopped);
+    });
+}
+
+#[test]
+fn instance_snapshots_report_running_and_idle_instances() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        let state = harness.state();
+        let result = harness.start().await.unwrap();
+        let idle = state.instance("idle").await.unwrap();
+
+        let mut snapshots = Vec::new();
+        for server in [harness.server().await, idle] {
+            snapshots.push(crate::snapshot(&server).await);
+        }
+
+        assert_eq!(snapshots[0].status, ServerStatus::Running);
+        assert_eq!(snapshots[0].pid, result.pid);
+        assert_eq!(snapshots[0].port, result.port);
+        assert!(snapshots[0].last_line.is_some());
+        assert_eq!(snapshots[1].status, ServerStatus::Stopped);
+        assert_eq!(snapshots[1].pid, None);
+    });
+}
+
+#[test]
+fn metrics_port_must_not_collide_with_any_instance() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        let state = harness.state();
+        let worker = state.instance("worker").await.unwrap();
+        *worker.port.lock().await = 4100;
+
+        let mut config = Config::clone(&*state.config().await);
+        config.metrics_port = Some(4100);
+        match state.validate(&config).await {
+            Err(ApiError::InvalidConfig { field, .. }) => assert_eq!(field, "metrics_port"),
+            other => panic!("expected an invalid metrics_port, got {:?}", other),
+        }
+        config.metrics_port = Some(4101);
+        state.validate(&config).await.unwrap();
+    });
+}
@@ -0,0 +1,97 @@
+use crate::logs::{LogBuffer, LogFilter, LogLevel, LogLine, LogSink, LogStream, LOG_BUFFER_LINES};
+use regex::Regex;
+
+fn line(text: impl Into<String>) -> LogLine {
+    LogLine {
+        instance: "default".to_string(),
+        stream: LogStream::Stdout,
+        line: text.into(),
+        ts: 0,
+        seq: 0,
+    }
+}
+
+#[test]
+fn pages_stay_put_while_lines_are_appended() {
+    let buffer = LogBuffer::default();
+    for i in 0..10 {
+        buffer.push(line(i.to_string()));
+    }
+    let first = buffer.page(0, 4);
+    assert_eq!(first.total, 10);
+    assert!(first.has_more);
+
+    for i in 10..20 {
+        buffer.push(line(i.to_string()));
+    }
+    let second = buffer.page(first.lines.last().unwrap().seq + 1, 4);
+    let texts: Vec<&str> = second.lines.iter().map(|l| l.line.as_str()).collect();
+    assert_eq!(texts, ["4", "5", "6", "7"]);
+    assert_eq!(second.total, 20);
+
+    let last = buffer.page(16, 10);
+    assert_eq!(last.lines.len(), 4);
+    assert!(!last.has_more);
+}
+
+#[test]
+fn pages_skip_lines_that_were_dropped() {
+    let buffer = LogBuffer::default();
+    for i in 0..LOG_BUFFER_LINES + 5 {
+        buffer.push(line(i.to_string()));
+    }
+    let page = buffer.page(0, 2);
+    assert_eq!(page.oldest, 5);
+    assert_eq!(page.lines[0].seq, 5);
+
+    buffer.clear();
+    buffer.push(line("after clear"));
+    let page = buffer.page(0, 10);
+    assert_eq!(page.lines.len(), 1);
+    assert_eq!(page.lines[0].seq, LOG_BUFFER_LINES as u64 + 5);
+}
+
+#[test]
+fn levels_are_read_off_common_prefixes() {
+    let cases = [
+        ("[WARN] disk almost full", LogLevel::Warn),
+        ("2024-05-01T12:00:00.000Z ERROR boom", LogLevel::Error),
+        ("debug: cache miss", LogLevel::Debug),
+        (r#"{"level":"trace","msg":"tick"}"#, LogLevel::Trace),
+        ("listening on 3000", LogLevel::Info),
+    ];
+    for (line, level) in cases {
+        assert_eq!(LogLevel::detect(line, None), level, "{line}");
+    }
+    let pattern = Regex::new(r"^<(?P<level>\w+)>").unwrap();
+    assert_eq!(
+        LogLevel::detect("<warning> error budget low", Some(&pattern)),
+        LogLevel::Warn
+    );
+}
+
+#[test]
+fn filtered_lines_skip_the_buffer() {
+    let sink = LogSink {
+        instance: "default".to_string(),
+        subscribers: Default::default(),
+        logs: LogBuffer::default(),
+        file: None,
+        redact: None,
+        filter: Default::default(),
+        level_pattern: None,
+        capture: Default::default(),
+    };
+    *sink.filter.lock().unwrap() = LogFilter {
+        streams: Some(vec![LogStream::Stderr]),
+        min_level: Some(LogLevel::Warn),
+    };
+    sink.write(LogStream::Stdout, "[ERROR] on stdout".to_string());
+    sink.write(LogStream::Stderr, "[DEBUG] noise".to_string());
+    sink.write(LogStream::Stderr, "plain".to_string());
+    sink.write(LogStream::Stderr, "[ERROR] kept".to_string());
+    sink.write(LogStream::System, "$ bun run".to_string());
+
+    let texts: Vec<String> = sink.logs.recent(None).into_iter().map(|l| l.line).collect();
+    assert_eq!(texts, ["[ERROR] kept", "$ bun run"]);
+}
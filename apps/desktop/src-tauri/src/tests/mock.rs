@@ -0,0 +1,308 @@
+use crate::spawner::ProcessSpawner;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Libtest name of `fake_server`, which the mock re-runs this binary as.
+const FAKE_SERVER_TEST: &str = "tests::mock::fake_server";
+const PORT_ENV: &str = "YEP_FAKE_PORT";
+const HOST_ENV: &str = "YEP_FAKE_HOST";
+const LISTEN_AFTER_ENV: &str = "YEP_FAKE_LISTEN_AFTER_MS";
+const EXIT_AFTER_ENV: &str = "YEP_FAKE_EXIT_AFTER_MS";
+const EXIT_CODE_ENV: &str = "YEP_FAKE_EXIT_CODE";
+const SUBPROCESS_ENV: &str = "YEP_FAKE_SUBPROCESS";
+const IGNORE_TERM_ENV: &str = "YEP_FAKE_IGNORE_TERM";
+const STATUS_ENV: &str = "YEP_FAKE_STATUS";
+const FLOOD_ENV: &str = "YEP_FAKE_FLOOD_LINES";
+const PATH_ENV: &str = "YEP_FAKE_PATH";
+/// Printed by a fake server with a subprocess, followed by the subprocess PID.
+pub const SUBPROCESS_LINE: &str = "fake server subprocess pid";
+
+/// How one spawned fake server behaves.
+#[derive(Clone, Copy, Debug)]
+pub struct FakeServer {
+    /// Delay before it starts answering HTTP; `None` never answers.
+    pub listen_after: Option<Duration>,
+    /// Exits with the code once the delay has passed.
+    pub exit_after: Option<(Duration, i32)>,
+    /// Starts a long `sleep` of its own, the way `bun run` starts the real server.
+    pub subprocess: bool,
+    /// Ignores SIGTERM, so only a force-kill stops it.
+    pub ignores_term: bool,
+    /// What every request is answered with.
+    pub status: u16,
+    /// Lines written to both stdout and stderr before it starts listening.
+    pub flood_lines: usize,
+    /// The only path it answers with `status`; anything else gets a 404.
+    pub path: Option<&'static str>,
+}
+
+impl FakeServer {
+    pub fn healthy() -> Self {
+        FakeServer {
+            listen_after: Some(Duration::ZERO),
+            exit_after: None,
+            subprocess: false,
+            ignores_term: false,
+            status: 200,
+            flood_lines: 0,
+            path: None,
+        }
+    }
+
+    pub fn never_ready() -> Self {
+        FakeServer {
+            listen_after: None,
+            exit_after: None,
+            subprocess: false,
+            ignores_term: false,
+            status: 200,
+            flood_lines: 0,
+            path: None,
+        }
+    }
+
+    /// Becomes ready right away, then exits with `code` after `after`.
+    pub fn crashes_after(after: Duration, code: i32) -> Self {
+        FakeServer {
+            listen_after: Some(Duration::ZERO),
+            exit_after: Some((after, code)),
+            subprocess: false,
+            ignores_term: false,
+            status: 200,
+            flood_lines: 0,
+            path: None,
+        }
+    }
+
+    /// Healthy, with a subprocess that only goes away if the whole tree is killed.
+    #[cfg(unix)]
+    pub fn with_subprocess() -> Self {
+        FakeServer {
+            subprocess: true,
+            ..FakeServer::healthy()
+        }
+    }
+
+    /// Healthy, but deaf to the graceful stop.
+    #[cfg(unix)]
+    pub fn ignoring_term() -> Self {
+        FakeServer {
+            ignores_term: true,
+            ..FakeServer::healthy()
+        }
+    }
+
+    /// Healthy once it has written `lines` lines to each pipe, far more than a pipe buffer
+    /// holds.
+    pub fn flooding(lines: usize) -> Self {
+        FakeServer {
+            flood_lines: lines,
+            ..FakeServer::healthy()
+        }
+    }
+
+    /// Healthy, but only at `path`.
+    pub fn serving_at(path: &'static str) -> Self {
+        FakeServer {
+            path: Some(path),
+            ..FakeServer::healthy()
+        }
+    }
+
+    /// Listening, but answering every request with `status`.
+    pub fn responding(status: u16) -> Self {
+        FakeServer {
+            status,
+            ..FakeServer::healthy()
+        }
+    }
+}
+
+/// Spawns this test binary as a fake server on the port the lifecycle picked, instead of
+/// the command it was given.
+pub struct MockSpawner {
+    /// Behaviors for the next spawns, in order; `fallback` once they run out.
+    queued: Mutex<VecDeque<FakeServer>>,
+    fallback: FakeServer,
+    pids: Mutex<Vec<u32>>,
+    /// The environment each command was given, as set on the `Command`.
+    envs: Mutex<Vec<HashMap<String, String>>>,
+}
+
+impl MockSpawner {
+    pub fn new(fallback: FakeServer) -> Self {
+        MockSpawner {
+            queued: Mutex::new(VecDeque::new()),
+            fallback,
+            pids: Mutex::new(Vec::new()),
+            envs: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Uses `first` for the next spawn before falling back.
+    pub fn then(self, first: FakeServer) -> Self {
+        self.queued.lock().unwrap().push_back(first);
+        self
+    }
+
+    /// Every PID spawned so far, oldest first.
+    pub fn pids(&self) -> Vec<u32> {
+        self.pids.lock().unwrap().clone()
+    }
+
+    /// The variables set on every command spawned so far, oldest first.
+    pub fn envs(&self) -> Vec<HashMap<String, String>> {
+        self.envs.lock().unwrap().clone()
+    }
+}
+
+impl ProcessSpawner for MockSpawner {
+    fn spawn(&self, command: &mut Command) -> io::Result<Child> {
+        let args: Vec<String> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let value = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|i| args.get(i + 1))
+                .cloned()
+                .unwrap_or_default()
+        };
+        let env = command
+            .get_envs()
+            .filter_map(|(key, value)| {
+                let value = value?.to_string_lossy().into_owned();
+                Some((key.to_string_lossy().into_owned(), value))
+            })
+            .collect();
+        self.envs.lock().unwrap().push(env);
+        let behavior = self
+            .queued
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or(self.fallback);
+
+        let mut fake = Command::new(std::env::current_exe()?);
+        fake.args([
+            "--exact",
+            FAKE_SERVER_TEST,
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .envs(
+            command
+                .get_envs()
+                .filter_map(|(key, value)| Some((key, value?))),
+        )
+        .env(PORT_ENV, value("--port"))
+        .env(HOST_ENV, value("--host"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+        if let Some(after) = behavior.listen_after {
+            fake.env(LISTEN_AFTER_ENV, after.as_millis().to_string());
+        }
+        if let Some((after, code)) = behavior.exit_after {
+            fake.env(EXIT_AFTER_ENV, after.as_millis().to_string())
+                .env(EXIT_CODE_ENV, code.to_string());
+        }
+        if behavior.subprocess {
+            fake.env(SUBPROCESS_ENV, "1");
+        }
+        if behavior.ignores_term {
+            fake.env(IGNORE_TERM_ENV, "1");
+        }
+        fake.env(STATUS_ENV, behavior.status.to_string());
+        if let Some(path) = behavior.path {
+            fake.env(PATH_ENV, path);
+        }
+        if behavior.flood_lines > 0 {
+            fake.env(FLOOD_ENV, behavior.flood_lines.to_string());
+        }
+        // Mirror the process group the real command would get.
+        crate::process::isolate(&mut fake);
+        let child = fake.spawn()?;
+        self.pids.lock().unwrap().push(child.id());
+        Ok(child)
+    }
+}
+
+fn env_ms(key: &str) -> Option<Duration> {
+    let ms = std::env::var(key).ok()?.parse().ok()?;
+    Some(Duration::from_millis(ms))
+}
+
+/// The fake server itself, run in a child spawned by `MockSpawner`. In the normal test
+/// run the environment isn't set and this passes without doing anything.
+#[test]
+fn fake_server() {
+    let Ok(port) = std::env::var(PORT_ENV) else {
+        return;
+    };
+    let host = std::env::var(HOST_ENV).unwrap_or_default();
+    let status = std::env::var(STATUS_ENV).unwrap_or_else(|_| "200".to_string());
+    let only_path = std::env::var(PATH_ENV).ok();
+    #[cfg(unix)]
+    if std::env::var_os(IGNORE_TERM_ENV).is_some() {
+        // SAFETY: ignoring a signal installs no handler code.
+        unsafe {
+            libc::signal(libc::SIGTERM, libc::SIG_IGN);
+        }
+    }
+    if std::env::var_os(SUBPROCESS_ENV).is_some() {
+        let mut sleep = Command::new("sleep").arg("60").spawn().unwrap();
+        println!("{} {}", SUBPROCESS_LINE, sleep.id());
+        std::thread::spawn(move || sleep.wait());
+    }
+    if let Some(after) = env_ms(EXIT_AFTER_ENV) {
+        let code = std::env::var(EXIT_CODE_ENV)
+            .ok()
+            .and_then(|code| code.parse().ok())
+            .unwrap_or(1);
+        std::thread::spawn(move || {
+            std::thread::sleep(after);
+            std::process::exit(code);
+        });
+    }
+    let Some(after) = env_ms(LISTEN_AFTER_ENV) else {
+        loop {
+            std::thread::sleep(Duration::from_secs(60));
+        }
+    };
+    std::thread::sleep(after);
+    let flood = std::env::var(FLOOD_ENV)
+        .ok()
+        .and_then(|lines| lines.parse().ok());
+    // About 100 bytes a line.
+    let padding = ".".repeat(80);
+    for i in 0..flood.unwrap_or(0) {
+        println!("flood out {} {}", i, padding);
+        eprintln!("flood err {} {}", i, padding);
+    }
+    let listener = TcpListener::bind((host.as_str(), port.parse().unwrap())).unwrap();
+    println!("fake server listening on {}:{}", host, port);
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let mut request = [0; 4096];
+        let read = stream.read(&mut request).unwrap_or(0);
+        let request = String::from_utf8_lossy(&request[..read]);
+        let path = request.split(' ').nth(1).unwrap_or_default();
+        let status = match &only_path {
+            Some(only) if only != path => "404",
+            _ => status.as_str(),
+        };
+        let response = format!(
+            "HTTP/1.1 {} Fake\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+            status
+        );
+        let _ = stream.write_all(response.as_bytes());
+    }
+}
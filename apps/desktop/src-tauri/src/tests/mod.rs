@@ -0,0 +1,184 @@
+mod config;
+mod lifecycle;
+mod logs;
+mod mock;
+mod net;
+mod pidfile;
+mod priority;
+mod process;
+mod settings;
+mod transitions;
+mod unwind;
+mod watcher;
+
+use crate::config::{Config, FileConfig, LaunchMode, RestartPolicy};
+use crate::error::ApiError;
+use crate::logs::LogSubscribers;
+use crate::process::Exit;
+use crate::server::{self, ServerHandle, ServerStatus, StartResult};
+use crate::{start, ApiState, DEFAULT_INSTANCE};
+use mock::MockSpawner;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, AppHandle, Manager};
+use tokio::sync::{Mutex, RwLock};
+
+/// A mock Tauri app managing an `ApiState` that spawns through a `MockSpawner`.
+struct Harness {
+    app: App<MockRuntime>,
+    spawner: Arc<MockSpawner>,
+}
+
+/// Held by tests that set `YEP_*` variables, which `Config::resolve` reads.
+static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn env_lock() -> std::sync::MutexGuard<'static, ()> {
+    ENV_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Settings that keep each test fast: an ephemeral port, quick polling and no restarts.
+fn test_config() -> Config {
+    let mut config = Config::resolve(&FileConfig::default(), None);
+    config.port = 0;
+    config.launch_mode = LaunchMode::Binary {
+        path: std::env::current_exe().unwrap(),
+        args: Vec::new(),
+    };
+    config.ready_banner = None;
+    config.startup_timeout_ms = 5000;
+    config.health_poll_interval_ms = 50;
+    config.health_probe_timeout_ms = 500;
+    config.stop_timeout_ms = 2000;
+    config.restart_policy = RestartPolicy::Never;
+    config.health_monitor = false;
+    config
+}
+
+impl Harness {
+    fn new(spawner: MockSpawner, configure: impl FnOnce(&mut Config)) -> Self {
+        let mut config = test_config();
+        configure(&mut config);
+        let spawner = Arc::new(spawner);
+        let app = mock_builder().build(mock_context(noop_assets())).unwrap();
+        app.manage(ApiState {
+            config: RwLock::new(Arc::new(config)),
+            config_path: None,
+            config_error: Mutex::new(None),
+            data_dir: None,
+            log_subscribers: LogSubscribers::default(),
+            log_filter: Default::default(),
+            log_file: None,
+            http: reqwest::Client::new(),
+            instances: Mutex::new(HashMap::new()),
+            persistent_token: Mutex::new(None),
+            health_monitor: AtomicBool::new(false),
+            spawner: spawner.clone(),
+            shutting_down: AtomicBool::new(false),
+            config_watcher: std::sync::Mutex::new(None),
+            metrics_server: std::sync::Mutex::new(None),
+        });
+        Harness { app, spawner }
+    }
+
+    fn handle(&self) -> &AppHandle<MockRuntime> {
+        self.app.handle()
+    }
+
+    fn state(&self) -> tauri::State<'_, ApiState> {
+        self.app.state::<ApiState>()
+    }
+
+    async fn server(&self) -> Arc<ServerHandle> {
+        self.state().instance(DEFAULT_INSTANCE).await.unwrap()
+    }
+
+    /// Starts the default instance the way `start_api_server` does.
+    async fn start(&self) -> Result<StartResult, ApiError> {
+        let state = self.state();
+        let server = self.server().await;
+        let options = state.launch_options().await;
+        start(self.handle(), &state, &server, options).await
+    }
+
+    /// Stops the default instance the way `stop_api_server` does.
+    async fn stop(&self) -> Result<Option<Exit>, ApiError> {
+        let state = self.state();
+        let server = self.server().await;
+        server::terminate(self.handle(), &state, &server).await
+    }
+
+    async fn status(&self) -> ServerStatus {
+        *self.server().await.status.lock().await
+    }
+
+    async fn has_child(&self) -> bool {
+        self.server().await.child.lock().await.is_some()
+    }
+
+    /// Polls until the default instance reaches `status`, giving up after `timeout`.
+    async fn wait_for(&self, status: ServerStatus, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if self.status().await == status {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        false
+    }
+
+    /// Runs watcher passes until one notices the child is gone.
+    async fn sweep_until_crashed(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut last_probe = Instant::now();
+        while Instant::now() < deadline {
+            crate::watcher::sweep(self.handle(), &mut last_probe).await;
+            if self.status().await != ServerStatus::Running {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        false
+    }
+}
+
+/// Kills whatever a test left running, without blocking inside the runtime.
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let state = self.state();
+        let Ok(instances) = state.instances.try_lock() else {
+            return;
+        };
+        for server in instances.values() {
+            server
+                .intentional_stop
+                .store(true, std::sync::atomic::Ordering::SeqCst);
+            if let Ok(mut child) = server.child.try_lock() {
+                if let Some(child) = child.as_mut() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+            }
+        }
+    }
+}
+
+fn is_alive(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+    // An exited process nobody has reaped yet is as good as dead.
+    system
+        .process(pid)
+        .is_some_and(|process| process.status() != ProcessStatus::Zombie)
+}
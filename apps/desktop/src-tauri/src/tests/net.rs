@@ -0,0 +1,14 @@
+use crate::net::authority;
+
+#[test]
+fn authority_brackets_ipv6_literals_only() {
+    let cases = [
+        ("::1", "[::1]:3838"),
+        ("0:0:0:0:0:0:0:1", "[0:0:0:0:0:0:0:1]:3838"),
+        ("localhost", "localhost:3838"),
+        ("127.0.0.1", "127.0.0.1:3838"),
+    ];
+    for (host, expected) in cases {
+        assert_eq!(authority(host, 3838), expected);
+    }
+}
@@ -0,0 +1,83 @@
+use super::is_alive;
+use crate::pidfile::{self, PidRecord};
+use std::process::{Child, Command};
+#[cfg(unix)]
+use std::time::{Duration, Instant};
+
+fn sleeper() -> Child {
+    #[cfg(unix)]
+    let mut command = {
+        let mut command = Command::new("sleep");
+        command.arg("60");
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("ping");
+        command.args(["-n", "60", "127.0.0.1"]);
+        command
+    };
+    command.spawn().unwrap()
+}
+
+fn pid_file(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("yep-{}-{}.pid", name, std::process::id()))
+}
+
+#[test]
+fn stale_pid_file_spares_a_process_that_reused_the_pid() {
+    let mut child = sleeper();
+    let path = pid_file("reused");
+    let mut record = PidRecord::now(child.id());
+    assert!(!record.command.is_empty());
+    // As if the PID had gone to an unrelated program since the file was written.
+    record.command = vec!["some-other-program".to_string()];
+    pidfile::write(&path, &record).unwrap();
+
+    assert_eq!(pidfile::kill_stale(&path), None);
+    assert!(is_alive(child.id()));
+    assert!(!path.exists());
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// A shell in its own process group, the way servers are spawned, with a `sleep` of its
+/// own standing in for what `bun run` starts. Returns the shell and the sleep's PID.
+#[cfg(unix)]
+fn server_with_subprocess() -> (Child, u32) {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    let mut command = Command::new("sh");
+    command
+        .args(["-c", "sleep 60 & echo $!; wait"])
+        .stdout(Stdio::piped());
+    crate::process::isolate(&mut command);
+    let mut child = command.spawn().unwrap();
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    (child, line.trim().parse().unwrap())
+}
+
+#[test]
+fn stale_pid_file_kills_the_process_it_recorded() {
+    #[cfg(unix)]
+    let (mut child, subprocess) = server_with_subprocess();
+    #[cfg(not(unix))]
+    let mut child = sleeper();
+    let path = pid_file("matching");
+    pidfile::write(&path, &PidRecord::now(child.id())).unwrap();
+
+    assert_eq!(pidfile::kill_stale(&path), Some(child.id()));
+    child.wait().unwrap();
+    #[cfg(unix)]
+    {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while is_alive(subprocess) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!is_alive(subprocess), "subprocess outlived the server");
+    }
+}
@@ -0,0 +1,48 @@
+use super::test_config;
+use crate::error::ApiError;
+use crate::priority;
+use std::process::Command;
+
+#[test]
+fn nice_outside_the_lowering_range_is_rejected() {
+    for nice in [-1, priority::MAX_NICE + 1] {
+        let mut config = test_config();
+        config.nice = nice;
+        assert!(
+            matches!(
+                config.validate(),
+                Err(ApiError::InvalidConfig { field: "nice", .. })
+            ),
+            "nice {} should be rejected",
+            nice
+        );
+    }
+}
+
+#[cfg(unix)]
+fn niceness(nice: i32) -> i32 {
+    let mut command = Command::new("sh");
+    command.args(["-c", "nice"]);
+    priority::lower(&mut command, nice);
+    let output = command.output().unwrap();
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn child_runs_at_the_configured_nice_value() {
+    let base = niceness(0);
+    assert_eq!(niceness(5), (base + 5).min(priority::MAX_NICE));
+}
+
+#[cfg(windows)]
+#[test]
+fn child_spawns_at_below_normal_priority() {
+    let mut command = Command::new("cmd");
+    command.args(["/C", "exit 0"]);
+    priority::lower(&mut command, 5);
+    assert!(command.status().unwrap().success());
+}
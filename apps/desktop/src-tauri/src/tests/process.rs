@@ -0,0 +1,92 @@
+use crate::process;
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+
+fn long_running() -> Child {
+    #[cfg(unix)]
+    let mut command = {
+        let mut command = Command::new("sleep");
+        command.arg("60");
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("ping");
+        command.args(["-n", "60", "127.0.0.1"]);
+        command
+    };
+    command.spawn().unwrap()
+}
+
+#[test]
+fn killing_an_exited_child_counts_as_success() {
+    tauri::async_runtime::block_on(async {
+        let mut child = long_running();
+        assert!(!process::kill(&mut child, 0).await.unwrap());
+        child.wait().unwrap();
+
+        let already_exited = process::kill(&mut child, 0).await.unwrap();
+        assert!(already_exited);
+    });
+}
+
+#[test]
+fn shutdown_never_waits_past_the_kill_timeout() {
+    tauri::async_runtime::block_on(async {
+        let mut child = long_running();
+        let began = Instant::now();
+        // Too short for the kill to be confirmed, which must fail rather than block.
+        let result = process::shutdown(&mut child, Duration::ZERO, Duration::ZERO, 0, || {}).await;
+        assert!(began.elapsed() < Duration::from_secs(1));
+        if let Err(e) = result {
+            assert!(matches!(e, process::StopError::Timeout), "{:?}", e);
+        }
+        let _ = process::kill_blocking(&mut child);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn stopped_child_is_reaped_within_the_grace_period() {
+    tauri::async_runtime::block_on(async {
+        let mut child = long_running();
+        let grace = Duration::from_secs(2);
+        let began = Instant::now();
+        let exit = process::shutdown(&mut child, grace, grace, 0, || {})
+            .await
+            .unwrap();
+
+        assert!(!exit.already_exited);
+        assert!(began.elapsed() < grace, "SIGTERM wasn't enough for sleep");
+        assert!(child.try_wait().unwrap().is_some());
+    });
+}
+
+/// Orphans are matched by a custom `process_match` pattern and killed.
+#[cfg(unix)]
+#[test]
+fn orphan_sweep_uses_the_configured_pattern() {
+    use crate::config::{Config, FileConfig};
+    use crate::orphans;
+
+    // Outlives the shell that started it, so it isn't counted as this process's child.
+    let marker = format!("{}", 100_000 + std::process::id());
+    let output = Command::new("sh")
+        .args(["-c", &format!("sleep {} >/dev/null 2>&1 & echo $!", marker)])
+        .output()
+        .unwrap();
+    let pid: u32 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .unwrap();
+
+    let mut config = Config::resolve(&FileConfig::default(), None);
+    config.process_match = Some(format!("^sleep {}$", marker));
+    config.validate().unwrap();
+    let found: Vec<u32> = orphans::find(&config, &[]).iter().map(|o| o.pid).collect();
+    assert_eq!(found, [pid]);
+    assert_eq!(orphans::kill(&[pid], &config, &[]), [pid]);
+
+    config.process_match = Some(".*".to_string());
+    assert!(config.validate().is_err());
+}
@@ -0,0 +1,65 @@
+use super::env_lock;
+use crate::config::{Config, FileConfig};
+use crate::settings::StoreSettings;
+use std::path::{Path, PathBuf};
+
+fn config_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("yep-{}-{}.toml", name, std::process::id()))
+}
+
+fn resolve(path: &Path, store: StoreSettings) -> (Config, Vec<&'static str>) {
+    let mut file = FileConfig::load(path).unwrap();
+    let shadowed = store.overlay(&mut file);
+    (Config::resolve(&file, None), shadowed)
+}
+
+#[test]
+fn port_edits_in_yep_toml_take_effect_unless_the_store_overrides_them() {
+    let _env = env_lock();
+    let path = config_path("port-edit");
+    std::fs::write(&path, "port = 4100\n").unwrap();
+    let (config, shadowed) = resolve(&path, StoreSettings::default());
+    assert_eq!(config.port, 4100);
+    assert!(shadowed.is_empty());
+
+    std::fs::write(&path, "port = 4200\n").unwrap();
+    let (config, shadowed) = resolve(&path, StoreSettings::default());
+    assert_eq!(config.port, 4200);
+    assert!(shadowed.is_empty());
+
+    let store = StoreSettings {
+        port: Some(4100),
+        ..StoreSettings::default()
+    };
+    let (config, shadowed) = resolve(&path, store);
+    assert_eq!(config.port, 4100);
+    assert_eq!(shadowed, ["port"]);
+
+    // A stored value matching the file hides nothing.
+    let store = StoreSettings {
+        port: Some(4200),
+        ..StoreSettings::default()
+    };
+    assert!(resolve(&path, store).1.is_empty());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn env_overrides_beat_the_store() {
+    let _env = env_lock();
+    let path = config_path("env-store");
+    std::fs::write(&path, "port = 4200\n").unwrap();
+    std::env::set_var("YEP_API_PORT", "4300");
+    let store = StoreSettings {
+        port: Some(4100),
+        ..StoreSettings::default()
+    };
+    let (config, _) = resolve(&path, store);
+    std::env::remove_var("YEP_API_PORT");
+    assert_eq!(config.port, 4300);
+
+    // Once the key is deleted from the store, the file applies again.
+    let (config, _) = resolve(&path, StoreSettings::default());
+    assert_eq!(config.port, 4200);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,153 @@
+use super::mock::{FakeServer, MockSpawner};
+use super::{is_alive, Harness};
+use crate::config::RestartPolicy;
+use crate::error::ApiError;
+use crate::server::ServerStatus;
+use std::time::Duration;
+
+#[test]
+fn start_from_stopped() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+        assert!(!harness.has_child().await);
+
+        harness.start().await.unwrap();
+        assert_eq!(harness.status().await, ServerStatus::Running);
+        assert!(harness.has_child().await);
+    });
+}
+
+#[test]
+fn start_while_running_keeps_the_child() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        harness.start().await.unwrap();
+        let pid = *harness.server().await.pid.lock().await;
+
+        let result = harness.start().await.unwrap();
+        assert!(result.already_running);
+        assert_eq!(result.pid, pid);
+        assert!(
+            result.message.contains("already running"),
+            "{}",
+            result.message
+        );
+        assert_eq!(harness.spawner.pids().len(), 1);
+        assert_eq!(harness.status().await, ServerStatus::Running);
+        assert_eq!(*harness.server().await.pid.lock().await, pid);
+        assert!(harness.has_child().await);
+    });
+}
+
+#[test]
+fn stop_while_running() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        harness.start().await.unwrap();
+
+        assert!(harness.stop().await.unwrap().is_some());
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+        assert!(!harness.has_child().await);
+        assert!(!is_alive(harness.spawner.pids()[0]));
+    });
+}
+
+#[test]
+fn stop_while_stopped() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+
+        assert!(harness.stop().await.unwrap().is_none());
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+        assert!(!harness.has_child().await);
+        assert!(harness.spawner.pids().is_empty());
+    });
+}
+
+#[test]
+fn cancel_during_starting() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::never_ready()), |_| {});
+        let cancel = async {
+            while !harness.has_child().await {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            assert_eq!(harness.status().await, ServerStatus::Starting);
+            assert!(harness.server().await.cancel_startup().await);
+        };
+        let (result, ()) = tokio::join!(harness.start(), cancel);
+
+        assert!(matches!(result, Err(ApiError::Cancelled)), "{:?}", result);
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+        assert!(!harness.has_child().await);
+        assert!(!is_alive(harness.spawner.pids()[0]));
+    });
+}
+
+#[test]
+fn crash_during_running_stays_crashed_without_restarts() {
+    tauri::async_runtime::block_on(async {
+        let fake = FakeServer::crashes_after(Duration::from_millis(300), 1);
+        let harness = Harness::new(MockSpawner::new(fake), |_| {});
+        harness.start().await.unwrap();
+
+        assert!(harness.sweep_until_crashed(Duration::from_secs(5)).await);
+        assert_eq!(harness.status().await, ServerStatus::Crashed);
+        assert!(!harness.has_child().await);
+        // `Never` leaves it crashed; give a restart the time it would have needed.
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(harness.status().await, ServerStatus::Crashed);
+        assert_eq!(harness.spawner.pids().len(), 1);
+    });
+}
+
+#[test]
+fn crash_is_followed_by_an_automatic_restart() {
+    tauri::async_runtime::block_on(async {
+        let spawner = MockSpawner::new(FakeServer::healthy())
+            .then(FakeServer::crashes_after(Duration::from_millis(300), 1));
+        let harness = Harness::new(spawner, |config| {
+            config.restart_policy = RestartPolicy::OnFailure { max_retries: 3 };
+        });
+        harness.start().await.unwrap();
+
+        assert!(harness.sweep_until_crashed(Duration::from_secs(5)).await);
+        assert_eq!(harness.status().await, ServerStatus::Crashed);
+        assert!(!harness.has_child().await);
+
+        assert!(
+            harness
+                .wait_for(ServerStatus::Running, Duration::from_secs(10))
+                .await
+        );
+        assert!(harness.has_child().await);
+        let server = harness.server().await;
+        assert_eq!(server.stats.lock().await.restart_count, 1);
+        assert_eq!(
+            *server.pid.lock().await,
+            harness.spawner.pids().get(1).copied()
+        );
+    });
+}
+
+#[test]
+fn stop_while_crashed_cancels_the_restart() {
+    tauri::async_runtime::block_on(async {
+        let spawner = MockSpawner::new(FakeServer::healthy())
+            .then(FakeServer::crashes_after(Duration::from_millis(300), 1));
+        let harness = Harness::new(spawner, |config| {
+            config.restart_policy = RestartPolicy::Always;
+        });
+        harness.start().await.unwrap();
+        assert!(harness.sweep_until_crashed(Duration::from_secs(5)).await);
+
+        assert!(harness.stop().await.unwrap().is_none());
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+        // Outlast the first backoff, after which the restart would have spawned.
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(harness.status().await, ServerStatus::Stopped);
+        assert!(!harness.has_child().await);
+        assert_eq!(harness.spawner.pids().len(), 1);
+    });
+}
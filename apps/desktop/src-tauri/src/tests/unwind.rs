@@ -0,0 +1,27 @@
+use super::mock::{FakeServer, MockSpawner};
+use super::Harness;
+use crate::error::ApiError;
+use crate::server::ServerStatus;
+use crate::unwind;
+
+#[test]
+fn panicking_command_becomes_an_error_and_leaves_the_instance_usable() {
+    tauri::async_runtime::block_on(async {
+        let harness = Harness::new(MockSpawner::new(FakeServer::healthy()), |_| {});
+        let server = harness.server().await;
+
+        let result: Result<(), ApiError> = unwind::guard(async {
+            let _status = server.status.lock().await;
+            panic!("deliberate test panic");
+        })
+        .await;
+
+        match result {
+            Err(ApiError::Internal(message)) => assert_eq!(message, "deliberate test panic"),
+            other => panic!("expected an internal error, got {:?}", other),
+        }
+        // The guard was dropped while unwinding, so the lock is free again.
+        assert_eq!(*server.status.try_lock().unwrap(), ServerStatus::Stopped);
+        assert_eq!(harness.start().await.unwrap().status, ServerStatus::Running);
+    });
+}
@@ -0,0 +1,21 @@
+use crate::watcher::{backoff, BACKOFF_BASE, BACKOFF_JITTER, BACKOFF_MAX};
+
+#[test]
+fn backoff_doubles_within_jitter_up_to_the_cap() {
+    for attempt in 0..40u32 {
+        let doublings = attempt.saturating_sub(1).min(16);
+        let base = BACKOFF_BASE
+            .saturating_mul(1u32 << doublings)
+            .min(BACKOFF_MAX);
+        let low = base.mul_f64(1.0 - BACKOFF_JITTER);
+        let high = base.mul_f64(1.0 + BACKOFF_JITTER);
+        for _ in 0..50 {
+            let delay = backoff(attempt);
+            assert!(
+                low <= delay && delay <= high,
+                "attempt {attempt}: {delay:?} outside {low:?}..={high:?}"
+            );
+        }
+    }
+    assert!(backoff(u32::MAX) <= BACKOFF_MAX.mul_f64(1.0 + BACKOFF_JITTER));
+}
@@ -0,0 +1,48 @@
+use rand::RngCore;
+
+/// Environment variable the child reads its auth token from.
+pub const TOKEN_ENV: &str = "YEP_API_TOKEN";
+pub const REDACTED: &str = "[redacted]";
+const KEYRING_SERVICE: &str = "yep-desktop";
+const KEYRING_USER: &str = "api-token";
+
+/// A fresh 256-bit token, hex-encoded.
+pub fn generate() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// `text` with every occurrence of `token` masked, for anything that ends up in logs.
+pub fn redact(text: &str, token: Option<&str>) -> String {
+    match token {
+        Some(token) if !token.is_empty() => text.replace(token, REDACTED),
+        _ => text.to_string(),
+    }
+}
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|e| e.to_string())
+}
+
+/// The token kept in the OS keyring, generating and storing one on first use. Blocks on
+/// the platform secret store, so call it off the async runtime.
+pub fn load_or_create() -> Result<String, String> {
+    let entry = keyring_entry()?;
+    match entry.get_password() {
+        Ok(token) if !token.is_empty() => Ok(token),
+        Ok(_) | Err(keyring::Error::NoEntry) => {
+            let token = generate();
+            entry.set_password(&token).map_err(|e| e.to_string())?;
+            Ok(token)
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Replaces the token kept in the OS keyring. Blocking, like `load_or_create`.
+pub fn store(token: &str) -> Result<(), String> {
+    keyring_entry()?
+        .set_password(token)
+        .map_err(|e| e.to_string())
+}
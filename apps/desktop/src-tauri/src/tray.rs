@@ -0,0 +1,150 @@
+use crate::events::{self, ServerEvent};
+use crate::server::{self, ServerStatus};
+use crate::{restart, start, ApiState, DEFAULT_INSTANCE};
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+const MAIN_WINDOW: &str = "main";
+
+/// Menu items whose enabled state follows the default server's status.
+struct TrayMenu {
+    start: MenuItem<Wry>,
+    stop: MenuItem<Wry>,
+    restart: MenuItem<Wry>,
+}
+
+/// Builds the tray icon and keeps it in sync with the default instance via server events.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let start = MenuItem::with_id(app, "start", "Start", true, None::<&str>)?;
+    let stop = MenuItem::with_id(app, "stop", "Stop", false, None::<&str>)?;
+    let restart = MenuItem::with_id(app, "restart", "Restart", false, None::<&str>)?;
+    let open = MenuItem::with_id(app, "open", "Open Window", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let menu = Menu::with_items(app, &[&start, &stop, &restart, &separator, &open, &quit])?;
+
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip(tooltip(ServerStatus::Stopped))
+        .on_menu_event(|app, event| on_menu(app, event.id.as_ref()));
+    if let Some(icon) = status_icon(app, ServerStatus::Stopped) {
+        tray = tray.icon(icon);
+    }
+    tray.build(app)?;
+    app.manage(TrayMenu {
+        start,
+        stop,
+        restart,
+    });
+
+    let handle = app.clone();
+    events::listen(app, move |_, _| {
+        let app = handle.clone();
+        tauri::async_runtime::spawn(async move { refresh(&app).await });
+    });
+    Ok(())
+}
+
+fn on_menu(app: &AppHandle, id: &str) {
+    match id {
+        "open" => show_main_window(app),
+        // Exiting stops the servers first, see the `ExitRequested` handler.
+        "quit" => app.exit(0),
+        "start" | "stop" | "restart" => {
+            tauri::async_runtime::spawn(run_action(app.clone(), id.to_string()));
+        }
+        _ => {}
+    }
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Runs a menu action through the same paths as the matching commands.
+pub async fn run_action(app: AppHandle, id: String) {
+    let state = app.state::<ApiState>();
+    let Ok(server) = state.instance(DEFAULT_INSTANCE).await else {
+        return;
+    };
+    let result = match id.as_str() {
+        "start" => {
+            let options = state.launch_options().await;
+            start(&app, &state, &server, options).await.map(drop)
+        }
+        "stop" => server::terminate(&app, &state, &server).await.map(drop),
+        _ => restart(&app, &state, &server).await.map(drop),
+    };
+    if let Err(e) = result {
+        let message = e.to_string();
+        events::emit(&app, &server.name, ServerEvent::StartFailed { message });
+    }
+}
+
+async fn refresh(app: &AppHandle) {
+    let state = app.state::<ApiState>();
+    let Ok(server) = state.existing(DEFAULT_INSTANCE).await else {
+        return;
+    };
+    let status = *server.status.lock().await;
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        use ServerStatus::*;
+        let _ = menu.start.set_enabled(matches!(status, Stopped | Crashed));
+        let _ = menu.stop.set_enabled(matches!(status, Starting | Running));
+        let _ = menu
+            .restart
+            .set_enabled(matches!(status, Running | Crashed));
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_icon(status_icon(app, status));
+        let _ = tray.set_tooltip(Some(tooltip(status)));
+    }
+}
+
+fn tooltip(status: ServerStatus) -> String {
+    let label = match status {
+        ServerStatus::Stopped => "stopped",
+        ServerStatus::Starting => "starting",
+        ServerStatus::Running => "running",
+        ServerStatus::Crashed => "crashed",
+        ServerStatus::Stopping => "stopping",
+    };
+    format!("yep API: {}", label)
+}
+
+/// The app icon with a status-colored dot in its bottom-right corner.
+fn status_icon(app: &AppHandle, status: ServerStatus) -> Option<Image<'static>> {
+    let base = app.default_window_icon()?;
+    let (width, height) = (base.width() as i64, base.height() as i64);
+    let color = match status {
+        ServerStatus::Running => [0x22, 0xc5, 0x5e],
+        ServerStatus::Starting | ServerStatus::Stopping => [0xf5, 0x9e, 0x0b],
+        ServerStatus::Crashed => [0xef, 0x44, 0x44],
+        ServerStatus::Stopped => [0x9c, 0xa3, 0xaf],
+    };
+    let radius = width.min(height) / 5;
+    let (cx, cy) = (width - radius - 1, height - radius - 1);
+    let mut rgba = base.rgba().to_vec();
+    if rgba.len() != (width * height * 4) as usize {
+        return Some(base.clone().to_owned());
+    }
+    for y in (cy - radius).max(0)..=cy + radius {
+        for x in (cx - radius).max(0)..=cx + radius {
+            let (dx, dy) = (x - cx, y - cy);
+            if dx * dx + dy * dy > radius * radius {
+                continue;
+            }
+            let i = ((y * width + x) * 4) as usize;
+            rgba[i..i + 3].copy_from_slice(&color);
+            rgba[i + 3] = 0xff;
+        }
+    }
+    Some(Image::new_owned(rgba, width as u32, height as u32))
+}
@@ -0,0 +1,47 @@
+use crate::error::ApiError;
+use std::any::Any;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Runs `future`, turning a panic anywhere inside it into `ApiError::Internal` so a bug
+/// in one command reaches the UI as an error instead of tearing down the IPC call.
+pub async fn guard<T, F>(future: F) -> Result<T, ApiError>
+where
+    F: Future<Output = Result<T, ApiError>>,
+{
+    CatchUnwind(Box::pin(future)).await
+}
+
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<T, F> Future for CatchUnwind<F>
+where
+    F: Future<Output = Result<T, ApiError>>,
+{
+    type Output = Result<T, ApiError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let future = self.0.as_mut();
+        // The future is dropped right after a panic, so no broken state is observed.
+        match catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(poll) => poll,
+            Err(payload) => {
+                let message = panic_message(payload.as_ref());
+                tracing::error!(%message, "command panicked");
+                Poll::Ready(Err(ApiError::Internal(message)))
+            }
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -0,0 +1,86 @@
+use crate::error::ApiError;
+use crate::net::Endpoint;
+use crate::proxy::{self, ApiRequest};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub const DEFAULT_VERSION_PATH: &str = "/version";
+pub const VERSION_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Deserialize)]
+struct BuildInfo {
+    version: String,
+    commit: Option<String>,
+    built_at: Option<String>,
+}
+
+/// What the server reports about its own build.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ServerVersion {
+    Known {
+        version: String,
+        commit: Option<String>,
+        built_at: Option<String>,
+        /// Whether `version` satisfies the configured range; `None` when no range is set
+        /// or the version isn't semver.
+        compatible: Option<bool>,
+    },
+    /// The server has no version endpoint, as with builds that predate it.
+    Unknown,
+}
+
+/// Fetches the build info from `path`, checking it against `expected` when given.
+pub async fn fetch(
+    client: &reqwest::Client,
+    endpoint: &Endpoint,
+    path: &str,
+    token: Option<&str>,
+    expected: Option<&str>,
+) -> Result<ServerVersion, ApiError> {
+    let request = ApiRequest {
+        method: "GET".to_string(),
+        path: path.to_string(),
+        body: None,
+        headers: HashMap::new(),
+    };
+    let response = tokio::time::timeout(
+        VERSION_TIMEOUT,
+        proxy::forward(client, endpoint, request, token),
+    )
+    .await
+    .map_err(|_| ApiError::RequestFailed("request timed out".to_string()))??;
+    if !(200..300).contains(&response.status) {
+        return Ok(ServerVersion::Unknown);
+    }
+    let Ok(info) = serde_json::from_str::<BuildInfo>(&response.body) else {
+        return Ok(ServerVersion::Unknown);
+    };
+    let compatible = expected.and_then(|range| is_compatible(&info.version, range));
+    if compatible == Some(false) {
+        tracing::warn!(
+            version = %info.version,
+            expected = expected.unwrap_or_default(),
+            "server version is outside the expected range"
+        );
+    }
+    Ok(ServerVersion::Known {
+        version: info.version,
+        commit: info.commit,
+        built_at: info.built_at,
+        compatible,
+    })
+}
+
+fn is_compatible(version: &str, range: &str) -> Option<bool> {
+    let version = Version::parse(version.trim_start_matches('v')).ok()?;
+    let range = VersionReq::parse(range).ok()?;
+    Some(range.matches(&version))
+}
+
+/// Whether `range` parses as a semver requirement like `^0.1` or `>=0.1.4, <0.2`.
+pub fn is_valid_range(range: &str) -> bool {
+    VersionReq::parse(range).is_ok()
+}
@@ -0,0 +1,255 @@
+use crate::config::ReadinessStrategy;
+use crate::error::ApiError;
+use crate::events::{self, ServerEvent};
+use crate::health::{self, ProbeState};
+use crate::server::{self, ServerHandle, ServerStatus};
+use crate::{ApiState, DEFAULT_INSTANCE};
+use rand::Rng;
+use serde::Serialize;
+use std::process::ExitStatus;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+pub const RESTARTED_EVENT: &str = "server-restarted";
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+pub const BACKOFF_BASE: Duration = Duration::from_secs(1);
+pub const BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// Fraction each delay is randomly stretched or shrunk by, so instances that crashed
+/// together don't all come back at the same moment.
+pub const BACKOFF_JITTER: f64 = 0.2;
+/// How long the server must stay up before its restart attempts are forgiven.
+const HEALTHY_COOLDOWN: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Serialize)]
+struct RestartedPayload {
+    instance: String,
+    attempt: u32,
+}
+
+/// Delay before the given restart attempt: 1s, 2s, 4s, ... capped at 30s, each within
+/// ±20% jitter.
+pub fn backoff(attempt: u32) -> Duration {
+    let factor = 1u32 << attempt.saturating_sub(1).min(16);
+    let delay = BACKOFF_BASE.saturating_mul(factor).min(BACKOFF_MAX);
+    let jitter = rand::thread_rng().gen_range(1.0 - BACKOFF_JITTER..=1.0 + BACKOFF_JITTER);
+    delay.mul_f64(jitter)
+}
+
+/// Watches every managed child for unexpected exits and respawns it according to its
+/// restart policy. Exits caused by `stop_api_server` are ignored via
+/// `ServerHandle::intentional_stop`; once the app is shutting down the watcher stops.
+pub async fn watch<R: Runtime>(app: AppHandle<R>) {
+    let mut last_probe = Instant::now();
+    loop {
+        tokio::time::sleep(WATCH_INTERVAL).await;
+        if shutting_down(&app) {
+            return;
+        }
+        sweep(&app, &mut last_probe).await;
+    }
+}
+
+/// Set once the app has started tearing the servers down, which must not bring them back.
+fn shutting_down<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.state::<ApiState>().shutting_down.load(Ordering::SeqCst)
+}
+
+/// One pass of the watcher: reaps crashed children and, when due, probes the live ones.
+pub(crate) async fn sweep<R: Runtime>(app: &AppHandle<R>, last_probe: &mut Instant) {
+    let state = app.state::<ApiState>();
+    // Exits during shutdown are the app's own doing, not crashes.
+    if state.shutting_down.load(Ordering::SeqCst) {
+        return;
+    }
+    let config = state.config().await;
+    let probe_due = state.health_monitor.load(Ordering::SeqCst)
+        && last_probe.elapsed() >= config.health_check_interval();
+    if probe_due {
+        *last_probe = Instant::now();
+    }
+    // An external server has no child to reap, but its health is still worth showing.
+    if config.is_external() {
+        if let (true, Ok(server)) = (probe_due, state.existing(DEFAULT_INSTANCE).await) {
+            check_probes(app, &state, server).await;
+        }
+        return;
+    }
+    for server in state.all_instances().await {
+        if *server.status.lock().await != ServerStatus::Running {
+            continue;
+        }
+        let exit = match server::reap_exited(&server).await {
+            Ok(Some(exit)) => exit,
+            Ok(None) => {
+                forgive_if_healthy(&server).await;
+                if probe_due {
+                    check_probes(app, &state, server).await;
+                }
+                continue;
+            }
+            Err(_) => continue,
+        };
+        if server.intentional_stop.load(Ordering::SeqCst) {
+            continue;
+        }
+        let error = ApiError::Crashed {
+            status: exit.to_string(),
+            stderr: server.stderr_tail(),
+        };
+        crashed(app, server, exit, error).await;
+    }
+}
+
+/// Resets the retry counter once the server has stayed up for the cooldown window,
+/// so intermittent crashes don't permanently exhaust the retry budget.
+async fn forgive_if_healthy(server: &ServerHandle) {
+    if server.restart_count.load(Ordering::SeqCst) == 0 {
+        return;
+    }
+    let started_at = *server.started_at.lock().await;
+    if started_at.is_some_and(|at| at.elapsed() >= HEALTHY_COOLDOWN) {
+        server.restart_count.store(0, Ordering::SeqCst);
+    }
+}
+
+/// Probes liveness and readiness, restarting a server that stays live but unready for
+/// longer than the configured limit, since that usually means it is wedged.
+async fn check_probes<R: Runtime>(app: &AppHandle<R>, state: &ApiState, server: Arc<ServerHandle>) {
+    let config = state.config().await;
+    let timeout = Duration::from_millis(config.health_probe_timeout_ms);
+    let started = Instant::now();
+    let (live, ready) = match config.readiness {
+        // Skipping the startup check doesn't make later problems any less worth showing.
+        ReadinessStrategy::HttpGet | ReadinessStrategy::None => {
+            let live = state
+                .probe_http(&config, &server, &config.health_path, timeout)
+                .await
+                .passed();
+            server.record_probe(started.elapsed(), live).await;
+            let ready = if config.ready_path == config.health_path {
+                live
+            } else {
+                state
+                    .probe_http(&config, &server, &config.ready_path, timeout)
+                    .await
+                    .passed()
+            };
+            (live, ready)
+        }
+        ReadinessStrategy::TcpConnect => {
+            let open = health::probe_connect(&server.endpoint(&config).await, timeout).await;
+            server.record_probe(started.elapsed(), open).await;
+            (open, open)
+        }
+        // Nothing to poll once the banner has been seen.
+        ReadinessStrategy::StdoutBanner => return,
+    };
+    let probes = ProbeState {
+        live: Some(live),
+        ready: Some(ready),
+    };
+    server.set_probes(app, probes).await;
+
+    let wedged = {
+        let mut since = server.not_ready_since.lock().await;
+        if live && !ready {
+            since.get_or_insert_with(Instant::now).elapsed() >= config.not_ready_limit()
+        } else {
+            *since = None;
+            false
+        }
+    };
+    // Restarting a wedged external server is up to whoever runs it.
+    if !wedged || config.is_external() {
+        return;
+    }
+    tracing::warn!(instance = %server.name, "server live but not ready; restarting");
+    let Ok(Some(exit)) = server::stop_child(app, state, &server).await else {
+        return;
+    };
+    let exit = exit.status;
+    let error = ApiError::Unresponsive {
+        not_ready_ms: config.not_ready_restart_ms,
+    };
+    crashed(app, server, exit, error).await;
+}
+
+/// Marks an unexpected exit, records why, and starts recovery in the background so one
+/// instance's backoff doesn't stall the others.
+async fn crashed<R: Runtime>(
+    app: &AppHandle<R>,
+    server: Arc<ServerHandle>,
+    exit: ExitStatus,
+    error: ApiError,
+) {
+    tracing::warn!(instance = %server.name, %exit, error = %error, "server crashed");
+    server.set_status(ServerStatus::Crashed).await;
+    server.record_error(&error).await;
+    server.record_crash(exit).await;
+    events::emit(
+        app,
+        &server.name,
+        ServerEvent::Crashed { code: exit.code() },
+    );
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move { recover(&app, server, exit).await });
+}
+
+#[tracing::instrument(skip_all, fields(instance = %server.name))]
+async fn recover<R: Runtime>(app: &AppHandle<R>, server: Arc<ServerHandle>, exit: ExitStatus) {
+    let state = app.state::<ApiState>();
+    loop {
+        let config = state.config().await;
+        let (crashes, window) = (
+            config.circuit_breaker_crashes,
+            config.circuit_breaker_window(),
+        );
+        if server.circuit_open(crashes, window).await {
+            tracing::warn!(
+                crashes,
+                "crashing repeatedly; not restarting until started manually"
+            );
+            server.set_status(ServerStatus::Crashed).await;
+            events::emit_circuit_open(app, &server.name, crashes, config.circuit_breaker_window_ms);
+            return;
+        }
+        let policy = *server.restart_policy.lock().await;
+        if !policy.should_restart(exit, server.restart_count.load(Ordering::SeqCst)) {
+            tracing::info!(?policy, "not restarting");
+            return;
+        }
+        let attempt = server.restart_count.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::time::sleep(backoff(attempt)).await;
+
+        // The user may have stopped or started the server manually while we waited, or
+        // quit the app.
+        if shutting_down(app)
+            || server.intentional_stop.load(Ordering::SeqCst)
+            || *server.status.lock().await != ServerStatus::Crashed
+        {
+            return;
+        }
+
+        tracing::info!(attempt, "restarting");
+        server.record_restart().await;
+        events::emit(app, &server.name, ServerEvent::Restarting { attempt });
+        match server::launch(app, &state, &server, state.launch_options().await).await {
+            Ok(_) => {
+                let payload = RestartedPayload {
+                    instance: server.name.clone(),
+                    attempt,
+                };
+                let _ = app.emit(RESTARTED_EVENT, payload);
+                return;
+            }
+            Err(ApiError::Cancelled) => return,
+            Err(e) => {
+                tracing::warn!(attempt, error = %e, "restart failed");
+                server.set_status(ServerStatus::Crashed).await;
+            }
+        }
+    }
+}
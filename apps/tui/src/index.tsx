@@ -73,7 +73,13 @@ switch (command) {
 		const { apiCommand } = await import("./mem/commands/api.ts");
 		const portFlag = process.argv.indexOf("--port");
 		const port = portFlag !== -1 ? Number(process.argv[portFlag + 1]) : 3838;
-		await apiCommand(port);
+		const hostFlag = process.argv.indexOf("--host");
+		const hostname =
+			hostFlag !== -1 ? process.argv[hostFlag + 1] : undefined;
+		const socketFlag = process.argv.indexOf("--socket");
+		const socket =
+			socketFlag !== -1 ? process.argv[socketFlag + 1] : undefined;
+		await apiCommand(port, hostname, socket);
 		break;
 	}
 	case "gui": {
@@ -137,6 +137,20 @@ const app = new Hono().basePath("/api");
 
 app.use("/*", cors({ origin: "*" }));
 
+// The desktop app passes a per-launch token; require it whenever one is set.
+const apiToken = process.env.YEP_API_TOKEN;
+if (apiToken) {
+	app.use("/*", async (c, next) => {
+		if (
+			c.req.method === "OPTIONS" ||
+			c.req.header("authorization") === `Bearer ${apiToken}`
+		) {
+			return next();
+		}
+		return c.json({ error: "Unauthorized" }, 401);
+	});
+}
+
 app.get("/health", (c) => c.json({ ok: true }));
 
 app.get("/status", async (c) => {
@@ -498,13 +512,25 @@ app.post("/debug/cleanup", async (c) => {
 
 export { app as apiApp };
 
-export function apiCommand(port = 3838): void {
+export function apiCommand(
+	port = 3838,
+	hostname?: string,
+	socket?: string
+): void {
 	if (!isInitialized()) {
 		console.error("Not initialized. Run 'yep enable' first.");
 		process.exit(1);
 	}
 
-	console.log(`Starting yep-mem API server on http://localhost:${port}`);
-	Bun.serve({ fetch: app.fetch, port });
-	console.log(`API server running at http://localhost:${port}`);
+	if (socket) {
+		console.log(`Starting yep-mem API server on unix:${socket}`);
+		Bun.serve({ fetch: app.fetch, unix: socket });
+		console.log(`API server running at unix:${socket}`);
+		return;
+	}
+
+	const host = hostname ?? "localhost";
+	console.log(`Starting yep-mem API server on http://${host}:${port}`);
+	Bun.serve({ fetch: app.fetch, port, hostname });
+	console.log(`API server running at http://${host}:${port}`);
 }